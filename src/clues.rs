@@ -1,40 +1,940 @@
 //! Clues and the Asset loader for them
-use bevy::{prelude::Component, reflect::TypeUuid, utils::HashMap};
-use serde::Deserialize;
+//!
+//! YAML anchors and aliases are resolved before clues are deserialized, so
+//! a field shared by several clues only has to be written once:
+//!
+//! ```yaml
+//! clues:
+//!   - id: stolen_ledger
+//!     persons: &smugglers [captain_rhys, old_mag]
+//!     locations: [docks]
+//!     information: The harbour ledger is missing.
+//!   - id: late_shipment
+//!     persons: *smugglers
+//!     locations: [warehouse]
+//!     information: A shipment arrived after curfew.
+//! ```
+//!
+//! Merge keys (`<<: *anchor`) are *not* supported.
+use std::{
+    borrow::Borrow,
+    collections::VecDeque,
+    fmt, fs,
+    io::{self, Read},
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use bevy::{
+    prelude::{info, Assets, EventReader, Res},
+    reflect::{FromReflect, Reflect, ReflectDeserialize, ReflectSerialize, TypeUuid},
+    utils::{HashMap, HashSet},
+};
+use serde::{
+    de::{self, value::MapAccessDeserializer, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 
 pub use assets::CluesAssetPlugin;
 
-use crate::locations::LocationId;
+use crate::{
+    locations::{Location, LocationError, LocationId, Locations},
+    maps::MapId,
+    persons::{Person, Persons},
+    yaml::{self, list_or_null},
+    CluesComponent, Mode,
+};
 
 /// A wrapper around a string to represent a person
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize)]
-#[serde(transparent)]
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[cfg_attr(not(feature = "strict-ids"), serde(transparent))]
+#[cfg_attr(feature = "strict-ids", serde(try_from = "String", into = "String"))]
 pub struct PersonId(String);
 
+#[cfg(feature = "strict-ids")]
+impl TryFrom<String> for PersonId {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        crate::validation::check_id(id).map(Self)
+    }
+}
+
+#[cfg(feature = "strict-ids")]
+impl From<PersonId> for String {
+    fn from(id: PersonId) -> Self {
+        id.0
+    }
+}
+
+impl Borrow<str> for PersonId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
 /// A wrapper around a string to represent a clue
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize)]
-#[serde(transparent)]
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[cfg_attr(not(feature = "strict-ids"), serde(transparent))]
+#[cfg_attr(feature = "strict-ids", serde(try_from = "String", into = "String"))]
 pub struct ClueId(String);
 
+#[cfg(feature = "strict-ids")]
+impl TryFrom<String> for ClueId {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        crate::validation::check_id(id).map(Self)
+    }
+}
+
+#[cfg(feature = "strict-ids")]
+impl From<ClueId> for String {
+    fn from(id: ClueId) -> Self {
+        id.0
+    }
+}
+
+impl ClueId {
+    /// The id of a clue that was loaded without one, until one is
+    /// generated by an [`IdStrategy`]
+    fn unassigned() -> Self {
+        ClueId(String::new())
+    }
+
+    fn is_unassigned(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Borrow<str> for ClueId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
 /// A clue, is a specfic peice of information that is linked
 /// to several locations and persons.
-#[derive(Debug, Deserialize)]
+///
+/// In a file a clue is either a map of its fields, or the compact
+/// `[id, information, [persons], [locations]]` array form with the rest
+/// of the fields left at their defaults.
+#[derive(Debug, Clone, Deserialize, Reflect)]
+#[serde(remote = "Self")]
 pub struct Clue {
-    /// The id of the clue
+    /// The id of the clue. This can be left out of a file to have one
+    /// generated, see [`IdStrategy`].
+    #[serde(default = "ClueId::unassigned")]
     pub id: ClueId,
     /// The locations relevant to the clue
     pub locations: Vec<LocationId>,
     /// The persons relevant to the clue
     pub persons: Vec<PersonId>,
     /// The actual information of the clue
-    pub information: String,
+    pub information: LocalizedText,
+    /// Whether the clue is known to the players
+    #[serde(default)]
+    pub known: bool,
+    /// How significant the clue is, higher is more important
+    #[serde(default = "default_importance")]
+    pub importance: u8,
+    /// Whether the clue is deliberate misdirection, these can be left
+    /// out of hints
+    #[serde(default)]
+    pub red_herring: bool,
+    /// Clues that need to be known before this one can be found
+    #[serde(default)]
+    pub requires: Vec<ClueId>,
+    /// Tags to categorise the clue by
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Other clues this one leads on to
+    #[serde(default)]
+    pub related_clues: Vec<ClueId>,
+    /// Other clues this one contradicts, e.g. when one of them is a lie.
+    /// A contradiction only needs to be declared on one of the clues.
+    #[serde(default)]
+    pub contradicts: Vec<ClueId>,
+    /// Any extra campaign specific fields, e.g. a skill check DC,
+    /// given in the file as a `meta:` map
+    #[serde(default, rename = "meta")]
+    pub metadata: HashMap<String, String>,
+    /// The in-game time at which the clue comes into play
+    #[serde(default)]
+    pub time: Option<i64>,
+    /// The in-game time from which the clue is relevant
+    #[serde(default)]
+    pub valid_from: Option<i64>,
+    /// The in-game time after which the clue is no longer relevant
+    #[serde(default)]
+    pub valid_until: Option<i64>,
+    /// Who wrote the clue, when several people share a campaign
+    #[serde(default)]
+    pub author: Option<String>,
+    /// The [`Clues::revision`] at which this clue was last modified
+    #[serde(skip)]
+    pub last_modified: u64,
+}
+
+impl<'de> Deserialize<'de> for Clue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ClueVisitor;
+        impl<'de> Visitor<'de> for ClueVisitor {
+            type Value = Clue;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a clue, or a [id, information, persons, locations] array")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Clue, A::Error> {
+                Clue::deserialize(MapAccessDeserializer::new(map))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Clue, A::Error> {
+                let id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let information = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let persons = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let locations = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                if seq.next_element::<IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(5, &self));
+                }
+                Ok(Clue {
+                    id,
+                    locations,
+                    persons,
+                    information,
+                    known: false,
+                    importance: default_importance(),
+                    red_herring: false,
+                    requires: Vec::new(),
+                    tags: Vec::new(),
+                    related_clues: Vec::new(),
+                    contradicts: Vec::new(),
+                    metadata: HashMap::new(),
+                    time: None,
+                    valid_from: None,
+                    valid_until: None,
+                    author: None,
+                    last_modified: 0,
+                })
+            }
+        }
+        deserializer.deserialize_any(ClueVisitor)
+    }
+}
+
+impl Clue {
+    /// Get the names of the fields that differ from another version of the
+    /// clue, ignoring the id and [`Clue::last_modified`]
+    fn changed_fields(&self, other: &Clue) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        let mut check = |name, changed| {
+            if changed {
+                fields.push(name);
+            }
+        };
+        check("locations", self.locations != other.locations);
+        check("persons", self.persons != other.persons);
+        check("information", self.information != other.information);
+        check("known", self.known != other.known);
+        check("importance", self.importance != other.importance);
+        check("red_herring", self.red_herring != other.red_herring);
+        check("requires", self.requires != other.requires);
+        check("tags", self.tags != other.tags);
+        check("related_clues", self.related_clues != other.related_clues);
+        check("contradicts", self.contradicts != other.contradicts);
+        check("meta", self.metadata != other.metadata);
+        check("time", self.time != other.time);
+        check("valid_from", self.valid_from != other.valid_from);
+        check("valid_until", self.valid_until != other.valid_until);
+        check("author", self.author != other.author);
+        fields
+    }
+
+    /// Get one of the clue's [`Clue::metadata`] values
+    pub fn metadata_get(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    /// Check whether the clue is relevant at an in-game time, a missing
+    /// bound leaves that end of the window open
+    pub fn is_active_at(&self, time: i64) -> bool {
+        self.valid_from.is_none_or(|from| from <= time)
+            && self.valid_until.is_none_or(|until| time <= until)
+    }
+
+    /// Get the information of the clue in a language, falling back to
+    /// the [`DEFAULT_LANGUAGE`] if it hasn't been translated
+    pub fn information_in(&self, lang: &str) -> Option<&str> {
+        self.information
+            .get(lang)
+            .or_else(|| self.information.get(DEFAULT_LANGUAGE))
+    }
+}
+
+/// The language plain, untranslated, text is in
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Text that is either plain, or translated into several languages.
+/// In a file it is either a string, or a map of language codes to strings:
+///
+/// ```yaml
+/// information:
+///   en: The door was forced.
+///   fr: La porte a été forcée.
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[reflect_value(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LocalizedText {
+    /// Text in the [`DEFAULT_LANGUAGE`]
+    Plain(String),
+    /// Text by language code
+    Localized(HashMap<String, String>),
+}
+
+impl LocalizedText {
+    /// Get the text in a language, with no fallback
+    pub fn get(&self, lang: &str) -> Option<&str> {
+        match self {
+            LocalizedText::Plain(text) if lang == DEFAULT_LANGUAGE => Some(text),
+            LocalizedText::Plain(_) => None,
+            LocalizedText::Localized(texts) => texts.get(lang).map(String::as_str),
+        }
+    }
+
+    /// Get the text in the language whose code sorts first, for when
+    /// there's nothing in the language wanted
+    pub fn first(&self) -> Option<&str> {
+        match self {
+            LocalizedText::Plain(text) => Some(text),
+            LocalizedText::Localized(texts) => texts
+                .iter()
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(_, text)| text.as_str()),
+        }
+    }
+
+    /// Get an [`Iterator`] over the text in every language
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let texts: Vec<&str> = match self {
+            LocalizedText::Plain(text) => vec![text],
+            LocalizedText::Localized(texts) => texts.values().map(String::as_str).collect(),
+        };
+        texts.into_iter()
+    }
+}
+
+impl From<String> for LocalizedText {
+    fn from(text: String) -> Self {
+        LocalizedText::Plain(text)
+    }
+}
+
+fn default_importance() -> u8 {
+    50
+}
+
+/// How the players discovered a clue
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiscoverySource {
+    /// The person who told them about the clue
+    pub person: Option<PersonId>,
+    /// The location the clue was found at
+    pub location: Option<LocationId>,
+    /// The in-game time it was discovered at
+    pub time: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct CluesFile {
+    #[serde(default, deserialize_with = "list_or_null")]
     clues: Vec<Clue>,
 }
 
+/// How to generate ids for clues that are loaded without one. Generated
+/// ids are unique within the file and never collide with the ids given
+/// in it, or across the files loaded by [`load_clues_dir`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// Number the clues in the order they appear, `clue_1`, `clue_2`, ...
+    #[default]
+    Sequential,
+    /// Hash the clue's information, so the id stays the same when clues
+    /// are reordered, e.g. `clue_3f2a9c0d1b7e4f65`
+    InformationHash,
+}
+
+impl CluesFile {
+    /// Give an id to every clue that doesn't have one
+    fn assign_ids(&mut self, strategy: IdStrategy) {
+        Self::assign_ids_across(std::slice::from_mut(self), strategy);
+    }
+
+    /// Give an id to every clue without one in several files that will be
+    /// merged, so the ids generated for one file never collide with the
+    /// ids given or generated in another
+    fn assign_ids_across(files: &mut [CluesFile], strategy: IdStrategy) {
+        let mut taken: HashSet<String> = files
+            .iter()
+            .flat_map(|file| &file.clues)
+            .filter(|c| !c.id.is_unassigned())
+            .map(|c| c.id.0.clone())
+            .collect();
+        let mut next = 1;
+        for file in files {
+            file.assign_ids_avoiding(strategy, &mut taken, &mut next);
+        }
+    }
+
+    /// Give an id to every clue that doesn't have one, avoiding `taken`.
+    /// Generated ids are added to `taken`, and `next` is the next number
+    /// to try for [`IdStrategy::Sequential`].
+    fn assign_ids_avoiding(
+        &mut self,
+        strategy: IdStrategy,
+        taken: &mut HashSet<String>,
+        next: &mut usize,
+    ) {
+        for clue in self.clues.iter_mut().filter(|c| c.id.is_unassigned()) {
+            let id = match strategy {
+                IdStrategy::Sequential => loop {
+                    let id = format!("clue_{}", next);
+                    *next += 1;
+                    if !taken.contains(&id) {
+                        break id;
+                    }
+                },
+                IdStrategy::InformationHash => {
+                    let mut hash = Fnv1a::new();
+                    let mut texts: Vec<_> = clue.information.iter().collect();
+                    texts.sort_unstable();
+                    for text in texts {
+                        hash.write_str(text);
+                    }
+                    let base = format!("clue_{:016x}", hash.finish());
+                    let mut id = base.clone();
+                    let mut n = 2;
+                    while taken.contains(&id) {
+                        id = format!("{}_{}", base, n);
+                        n += 1;
+                    }
+                    id
+                }
+            };
+            taken.insert(id.clone());
+            clue.id = ClueId(id);
+        }
+    }
+}
+
+/// Which clues to get from [`Clues::page`]
+#[derive(Debug, Clone)]
+pub enum ClueQuery {
+    /// Every clue
+    All,
+    /// Clues linked to a person, see [`Clues::get_by_person`]
+    ByPerson(PersonId),
+    /// Clues linked to a location, see [`Clues::get_by_location`]
+    ByLocation(LocationId),
+    /// Clues linked to both a person and a location, see
+    /// [`Clues::get_by_person_and_location`]
+    ByPersonAndLocation(PersonId, LocationId),
+    /// Clues matching a [`ClueQueryBuilder`]
+    Matching(ClueQueryBuilder),
+}
+
+impl ClueQuery {
+    /// Start building a query out of several conditions
+    pub fn builder() -> ClueQueryBuilder {
+        Default::default()
+    }
+}
+
+/// A composable filter over [`Clues`], made with [`ClueQuery::builder`].
+/// Each condition added narrows the query down further, a clue has to
+/// match all of them.
+#[derive(Debug, Default, Clone)]
+pub struct ClueQueryBuilder {
+    persons: Vec<PersonId>,
+    locations: Vec<LocationId>,
+    tags: Vec<String>,
+    known: Option<bool>,
+}
+
+impl ClueQueryBuilder {
+    /// Only match clues linked to a person
+    pub fn person(mut self, person: PersonId) -> Self {
+        self.persons.push(person);
+        self
+    }
+
+    /// Only match clues linked to a location
+    pub fn location(mut self, location: LocationId) -> Self {
+        self.locations.push(location);
+        self
+    }
+
+    /// Only match clues with a tag
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Only match known, or unknown, clues
+    pub fn known(mut self, known: bool) -> Self {
+        self.known = Some(known);
+        self
+    }
+
+    /// Check whether a single clue matches the query
+    pub fn matches(&self, clue: &Clue) -> bool {
+        self.persons.iter().all(|p| clue.persons.contains(p))
+            && self.locations.iter().all(|l| clue.locations.contains(l))
+            && self.tags.iter().all(|t| clue.tags.contains(t))
+            && self.known.is_none_or(|known| clue.known == known)
+    }
+
+    /// Get all the clues matching the query, each clue is only
+    /// returned once
+    pub fn execute<'a>(&'a self, clues: &'a Clues) -> impl Iterator<Item = &'a Clue> {
+        #[cfg(feature = "trace")]
+        let span = crate::trace::QuerySpan::start("execute");
+        // Start from one of the indices if we can, rather than
+        // checking every clue
+        let candidates: Box<dyn Iterator<Item = &'a Clue> + 'a> =
+            if let Some(person) = self.persons.first() {
+                Box::new(clues.get_by_person(person))
+            } else if let Some(location) = self.locations.first() {
+                Box::new(clues.get_by_location(location))
+            } else if let Some(tag) = self.tags.first() {
+                Box::new(clues.get_by_tag(tag))
+            } else {
+                Box::new(clues.clues.values())
+            };
+        let mut seen = HashSet::new();
+        let clues = candidates.filter(move |c| self.matches(c) && seen.insert(&c.id));
+        #[cfg(feature = "trace")]
+        let clues = span.finish(clues);
+        clues
+    }
+}
+
+/// A boolean expression over clues, for filters that a [`ClueQueryBuilder`]
+/// can't express. Use with [`Clues::query_expr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClueFilter {
+    /// Matches if all of the filters match, or if there are none
+    And(Vec<ClueFilter>),
+    /// Matches if any of the filters match
+    Or(Vec<ClueFilter>),
+    /// Matches if the filter doesn't
+    Not(Box<ClueFilter>),
+    /// Matches clues linked to the person
+    HasPerson(PersonId),
+    /// Matches clues linked to the location
+    HasLocation(LocationId),
+    /// Matches clues with the tag
+    HasTag(String),
+    /// Matches clues whose `known` flag is the given value
+    Known(bool),
+}
+
+impl ClueFilter {
+    /// Check whether a clue matches the filter
+    pub fn matches(&self, clue: &Clue) -> bool {
+        match self {
+            ClueFilter::And(filters) => filters.iter().all(|f| f.matches(clue)),
+            ClueFilter::Or(filters) => filters.iter().any(|f| f.matches(clue)),
+            ClueFilter::Not(filter) => !filter.matches(clue),
+            ClueFilter::HasPerson(person) => clue.persons.contains(person),
+            ClueFilter::HasLocation(location) => clue.locations.contains(location),
+            ClueFilter::HasTag(tag) => clue.tags.contains(tag),
+            ClueFilter::Known(known) => clue.known == *known,
+        }
+    }
+}
+
+/// Everything known about one person, for exporting to other tools
+#[derive(Debug, Serialize)]
+pub struct PersonDossier {
+    /// The person the dossier is about
+    pub person: PersonId,
+    /// The clues linked to the person, ordered by id
+    pub clues: Vec<DossierClue>,
+    /// The locations from the person's clues, ordered by id
+    pub locations: Vec<DossierLocation>,
+    /// The other persons mentioned in the person's clues, ordered by id
+    pub associates: Vec<PersonId>,
+}
+
+/// A clue in a [`PersonDossier`]
+#[derive(Debug, Serialize)]
+pub struct DossierClue {
+    /// The id of the clue
+    pub id: ClueId,
+    /// The information of the clue
+    pub information: LocalizedText,
+    /// Whether the clue is known to the players
+    pub known: bool,
+}
+
+/// A location in a [`PersonDossier`]
+#[derive(Debug, Serialize)]
+pub struct DossierLocation {
+    /// The id of the location
+    pub id: LocationId,
+    /// The name of the location, if it could be found
+    pub name: Option<String>,
+}
+
+/// A clue with its persons and locations looked up, for displaying it,
+/// see [`resolve_clue`]
+#[derive(Debug, Clone)]
+pub struct ResolvedClue<'a> {
+    /// The id of the clue
+    pub id: &'a ClueId,
+    /// The information of the clue
+    pub information: &'a LocalizedText,
+    /// The persons of the clue that could be found, in the clue's order
+    pub persons: Vec<&'a Person>,
+    /// The locations of the clue that could be found, in the clue's order
+    pub locations: Vec<&'a Location>,
+}
+
+/// The differences between two versions of some [`Clues`], see
+/// [`Clues::diff`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClueDiff {
+    /// The clues only in the new version
+    pub added: Vec<ClueId>,
+    /// The clues only in the old version
+    pub removed: Vec<ClueId>,
+    /// The clues in both versions that don't match
+    pub changed: Vec<ClueChange>,
+}
+
+/// A clue that was edited between two versions, in a [`ClueDiff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClueChange {
+    /// The id of the clue
+    pub id: ClueId,
+    /// The names of the fields that changed, as written in the file,
+    /// e.g. `"information"`
+    pub fields: Vec<&'static str>,
+}
+
+/// Errors that can happen when loading or combining clues
+#[derive(Debug)]
+pub enum ClueError {
+    /// Reading a file failed
+    Io(io::Error),
+    /// A file wasn't valid YAML, or didn't describe what it should
+    Yaml(serde_yaml::Error),
+    /// The same [`ClueId`] was used by more than one clue
+    DuplicateId(ClueId),
+    /// A CSV file was malformed or missing a required column
+    Csv(String),
+    /// The locations couldn't be loaded
+    Location(LocationError),
+    /// A clue references a person that doesn't exist
+    UnknownPerson {
+        /// The clue with the reference
+        clue: ClueId,
+        /// The person that doesn't exist
+        person: PersonId,
+    },
+    /// A clue references a location that doesn't exist
+    UnknownLocation {
+        /// The clue with the reference
+        clue: ClueId,
+        /// The location that doesn't exist
+        location: LocationId,
+    },
+    /// A clue requires, relates to or contradicts a clue that doesn't exist
+    UnknownClue {
+        /// The clue with the reference
+        clue: ClueId,
+        /// The clue that doesn't exist
+        referenced: ClueId,
+    },
+    /// A clue ends up requiring itself, so it can never be found
+    RequiresCycle(ClueId),
+    /// A location ends up being its own parent
+    LocationCycle(LocationId),
+}
+
+impl fmt::Display for ClueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClueError::Io(e) => write!(f, "failed to read clues: {}", e),
+            ClueError::Yaml(e) => write!(f, "failed to parse YAML: {}", e),
+            ClueError::DuplicateId(id) => write!(f, "duplicate clue id {:?}", id.0),
+            ClueError::Csv(e) => write!(f, "failed to parse clues CSV: {}", e),
+            ClueError::Location(e) => write!(f, "failed to load locations: {}", e),
+            ClueError::UnknownPerson { clue, person } => {
+                write!(f, "clue {:?} has unknown person {:?}", clue.0, person.0)
+            }
+            ClueError::UnknownLocation { clue, location } => {
+                let location: &str = location.borrow();
+                write!(f, "clue {:?} has unknown location {:?}", clue.0, location)
+            }
+            ClueError::UnknownClue { clue, referenced } => {
+                write!(
+                    f,
+                    "clue {:?} references unknown clue {:?}",
+                    clue.0, referenced.0
+                )
+            }
+            ClueError::RequiresCycle(id) => write!(f, "clue {:?} requires itself", id.0),
+            ClueError::LocationCycle(id) => {
+                let id: &str = id.borrow();
+                write!(f, "location {:?} is its own ancestor", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClueError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClueError::Io(e) => Some(e),
+            ClueError::Yaml(e) => Some(e),
+            ClueError::Location(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ClueError {
+    fn from(e: io::Error) -> Self {
+        ClueError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for ClueError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ClueError::Yaml(e)
+    }
+}
+
+impl From<LocationError> for ClueError {
+    fn from(e: LocationError) -> Self {
+        ClueError::Location(e)
+    }
+}
+
+/// Get the paths of all the files in a directory with names ending in
+/// `suffix`, sorted so that which file an error is reported against is stable
+fn files_with_suffix(path: &Path, suffix: &str) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        let matches = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(suffix));
+        if matches && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Load every `*.clues.yml` file in a directory and merge them into one
+/// [`Clues`]. This doesn't go through the asset server so can be used
+/// by tooling. A [`ClueError::DuplicateId`] is returned if two files
+/// share a clue id. Clues without ids are numbered across all the files.
+pub fn load_clues_dir(path: &Path) -> Result<Clues, ClueError> {
+    let mut files = Vec::new();
+    for path in files_with_suffix(path, ".clues.yml")? {
+        files.push(yaml::from_slice::<CluesFile>(&fs::read(path)?)?);
+    }
+    CluesFile::assign_ids_across(&mut files, IdStrategy::default());
+    let mut clues = Clues::new();
+    for clues_file in files {
+        clues.merge(clues_file.into())?;
+    }
+    Ok(clues)
+}
+
+/// Check a whole campaign directory without starting the game. All the
+/// `*.clues.yml`, `*.locations.yml` and `*.persons.yml` files are loaded
+/// and checked for references to things that don't exist and for cycles.
+/// Every problem found is returned, not just the first.
+pub fn validate_campaign_dir(path: &Path) -> Result<(), Vec<ClueError>> {
+    let mut errors = Vec::new();
+    let mut read_all = |suffix: &str| -> Vec<Vec<u8>> {
+        let paths = match files_with_suffix(path, suffix) {
+            Ok(paths) => paths,
+            Err(e) => {
+                errors.push(e.into());
+                return Vec::new();
+            }
+        };
+        paths
+            .into_iter()
+            .filter_map(|path| fs::read(path).map_err(|e| errors.push(e.into())).ok())
+            .collect()
+    };
+    let (clue_files, location_files, person_files) = (
+        read_all(".clues.yml"),
+        read_all(".locations.yml"),
+        read_all(".persons.yml"),
+    );
+
+    let mut files: Vec<CluesFile> = clue_files
+        .iter()
+        .filter_map(|bytes| {
+            yaml::from_slice(bytes)
+                .map_err(|e| errors.push(e.into()))
+                .ok()
+        })
+        .collect();
+    CluesFile::assign_ids_across(&mut files, IdStrategy::default());
+    let mut clues = Clues::new();
+    for file in files {
+        if let Err(e) = clues.merge(file.into()) {
+            errors.push(e);
+        }
+    }
+    let (locations, location_errors) = crate::locations::load_files(&location_files);
+    errors.extend(location_errors);
+    let (persons, person_errors) = crate::persons::load_files(&person_files);
+    errors.extend(person_errors);
+
+    let mut sorted: Vec<_> = clues.clues.values().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+    for clue in &sorted {
+        for person in clue.persons.iter().filter(|p| persons.get(p).is_none()) {
+            errors.push(ClueError::UnknownPerson {
+                clue: clue.id.clone(),
+                person: person.clone(),
+            });
+        }
+        for location in clue.locations.iter().filter(|l| locations.get(l).is_none()) {
+            errors.push(ClueError::UnknownLocation {
+                clue: clue.id.clone(),
+                location: location.clone(),
+            });
+        }
+        let referenced = clue
+            .requires
+            .iter()
+            .chain(&clue.related_clues)
+            .chain(&clue.contradicts);
+        for other in referenced.filter(|r| clues.get(r).is_none()) {
+            errors.push(ClueError::UnknownClue {
+                clue: clue.id.clone(),
+                referenced: other.clone(),
+            });
+        }
+    }
+    for clue in &sorted {
+        if clues.requires_itself(&clue.id) {
+            errors.push(ClueError::RequiresCycle(clue.id.clone()));
+        }
+    }
+    let mut location_ids: Vec<_> = locations.iter().map(|l| &l.id).collect();
+    location_ids.sort();
+    for id in location_ids {
+        if locations.is_own_ancestor(id) {
+            errors.push(ClueError::LocationCycle(id.clone()));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Load clues from a CSV file, e.g. one exported from a spreadsheet.
+///
+/// The first row must be a header naming the columns, which can be in any
+/// order: `id`, `information`, `persons` and `locations`. Persons and
+/// locations are separated by `;`. Other columns are ignored and every
+/// other field of the clues is left at its default.
+pub fn load_clues_csv(bytes: &[u8]) -> Result<Clues, ClueError> {
+    let text = std::str::from_utf8(bytes).map_err(|e| ClueError::Csv(e.to_string()))?;
+    let mut records = crate::csv::parse(text).map_err(ClueError::Csv)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| ClueError::Csv("missing header row".to_string()))?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim() == name)
+            .ok_or_else(|| ClueError::Csv(format!("missing {:?} column", name)))
+    };
+    let (id_col, info_col) = (column("id")?, column("information")?);
+    let (persons_col, locations_col) = (column("persons")?, column("locations")?);
+
+    let list = |field: &str| -> serde_yaml::Value {
+        field
+            .split(';')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| serde_yaml::Value::String(s.to_string()))
+            .collect::<Vec<_>>()
+            .into()
+    };
+
+    let mut clues = Clues::new();
+    for (row, record) in records.enumerate() {
+        let field = |col: usize| record.get(col).map(String::as_str).unwrap_or_default();
+        if field(id_col).trim().is_empty() {
+            return Err(ClueError::Csv(format!("row {} has no id", row + 1)));
+        }
+        // Go through serde so the rest of the fields get their defaults
+        let mut clue = serde_yaml::Mapping::new();
+        clue.insert("id".into(), field(id_col).trim().into());
+        clue.insert("information".into(), field(info_col).into());
+        clue.insert("persons".into(), list(field(persons_col)));
+        clue.insert("locations".into(), list(field(locations_col)));
+        let clue: Clue = serde_yaml::from_value(clue.into())?;
+        if clues.get(&clue.id).is_some() {
+            return Err(ClueError::DuplicateId(clue.id));
+        }
+        clues.insert(clue);
+    }
+    Ok(clues)
+}
+
+/// Look up the persons and locations of a clue. Ids that can't be found
+/// are left out.
+pub fn resolve_clue<'a>(
+    clue: &'a Clue,
+    locations: &'a Locations,
+    persons: &'a Persons,
+) -> ResolvedClue<'a> {
+    ResolvedClue {
+        id: &clue.id,
+        information: &clue.information,
+        persons: clue.persons.iter().filter_map(|p| persons.get(p)).collect(),
+        locations: clue
+            .locations
+            .iter()
+            .filter_map(|l| locations.get(l))
+            .collect(),
+    }
+}
+
 /// A holder for many clues, allows you to lookup clues
 /// by a common location or place
 #[derive(Debug, Default, TypeUuid)]
@@ -43,10 +943,18 @@ pub struct Clues {
     clues: HashMap<ClueId, Clue>,
     by_person: HashMap<PersonId, Vec<ClueId>>,
     by_location: HashMap<LocationId, Vec<ClueId>>,
+    by_tag: HashMap<String, Vec<ClueId>>,
+    discovery: HashMap<ClueId, DiscoverySource>,
+    revision: u64,
+    /// The clues that were removed and the revision they were removed at,
+    /// oldest first
+    removed: Vec<(ClueId, u64)>,
 }
 
 impl From<CluesFile> for Clues {
-    fn from(clues_file: CluesFile) -> Self {
+    /// Clues without ids are given them with [`IdStrategy::Sequential`]
+    fn from(mut clues_file: CluesFile) -> Self {
+        clues_file.assign_ids(IdStrategy::default());
         let mut clues = Self::new();
         for clue in clues_file.clues {
             clues.insert(clue)
@@ -61,9 +969,28 @@ impl Clues {
         Default::default()
     }
 
+    /// Load clues from a clues file being read from a [`Read`]er, e.g.
+    /// a socket, rather than a buffer
+    pub fn from_reader<R: Read>(reader: R) -> Result<Clues, ClueError> {
+        let clues_file: CluesFile = yaml::from_reader(reader)?;
+        Ok(clues_file.into())
+    }
+
+    /// Stop the clues from being changed any more, so they can be cloned
+    /// cheaply, see [`FrozenClues`]
+    pub fn freeze(self) -> FrozenClues {
+        FrozenClues(Arc::new(self))
+    }
+
     /// Insert a new clue
-    pub fn insert(&mut self, clue: Clue) {
-        for person in &clue.persons {
+    pub fn insert(&mut self, mut clue: Clue) {
+        self.revision += 1;
+        clue.last_modified = self.revision;
+        if !self.removed.is_empty() {
+            self.removed.retain(|(id, _)| id != &clue.id);
+        }
+        // A clue listing something twice is still only indexed once under it
+        for person in clue.persons.iter().collect::<HashSet<_>>() {
             match self.by_person.get_mut(person) {
                 Some(peeps) => {
                     peeps.push(clue.id.clone());
@@ -73,7 +1000,7 @@ impl Clues {
                 }
             }
         }
-        for location in &clue.locations {
+        for location in clue.locations.iter().collect::<HashSet<_>>() {
             match self.by_location.get_mut(location) {
                 Some(locs) => {
                     locs.push(clue.id.clone());
@@ -84,77 +1011,1382 @@ impl Clues {
                 }
             }
         }
+        for tag in clue.tags.iter().collect::<HashSet<_>>() {
+            match self.by_tag.get_mut(tag) {
+                Some(tagged) => {
+                    tagged.push(clue.id.clone());
+                }
+                None => {
+                    self.by_tag.insert(tag.clone(), vec![clue.id.clone()]);
+                }
+            }
+        }
         self.clues.insert(clue.id.clone(), clue);
     }
 
-    /// Get a reference to a clue by it's [`ClueId`].
-    pub fn get(&self, clue: &ClueId) -> Option<&Clue> {
-        self.clues.get(clue)
+    /// Move all the clues from `other` into this collection. If any of
+    /// the ids are already present nothing is moved and a
+    /// [`ClueError::DuplicateId`] is returned.
+    pub fn merge(&mut self, mut other: Clues) -> Result<(), ClueError> {
+        if let Some(id) = other.clues.keys().find(|id| self.clues.contains_key(*id)) {
+            return Err(ClueError::DuplicateId(id.clone()));
+        }
+        self.discovery.extend(other.discovery.drain());
+        for (_, clue) in other.clues.drain() {
+            self.insert(clue);
+        }
+        Ok(())
     }
 
-    /// Get a mutable reference to a clue by it's [`ClueId`].
-    pub fn get_mut(&mut self, clue: &ClueId) -> Option<&mut Clue> {
-        self.clues.get_mut(clue)
+    /// Remove every clue that references persons or locations, but none
+    /// that exist. Clues without any references are kept, see
+    /// [`Clues::unreachable`] for those. Returns the removed ids.
+    pub fn prune_dangling(&mut self, locations: &Locations, persons: &Persons) -> Vec<ClueId> {
+        let mut dangling: Vec<ClueId> = self
+            .clues
+            .values()
+            .filter(|c| !c.persons.is_empty() || !c.locations.is_empty())
+            .filter(|c| {
+                c.persons.iter().all(|p| persons.get(p).is_none())
+                    && c.locations.iter().all(|l| locations.get(l).is_none())
+            })
+            .map(|c| c.id.clone())
+            .collect();
+        dangling.sort();
+        for id in &dangling {
+            self.remove(id);
+        }
+        dangling
     }
 
-    /// Get all clues by a [`LocationId`]
-    pub fn get_by_location(&self, location: &LocationId) -> impl Iterator<Item = &Clue> {
-        self.by_location
-            .get(location)
+    /// Check whether a clue ends up requiring itself through
+    /// [`Clue::requires`]
+    fn requires_itself(&self, id: &ClueId) -> bool {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<&ClueId> = self
+            .clues
+            .get(id)
             .into_iter()
-            .flatten()
-            .filter_map(|id| self.clues.get(id))
+            .flat_map(|c| &c.requires)
+            .collect();
+        while let Some(next) = queue.pop_front() {
+            if next == id {
+                return true;
+            }
+            if seen.insert(next) {
+                queue.extend(self.clues.get(next).into_iter().flat_map(|c| &c.requires));
+            }
+        }
+        false
     }
 
-    /// Get all clues by a [`PersonId`], also takes an option
-    /// that if set to [`Some`] decides whether to only get known
-    /// or unknown clues
-    pub fn get_by_person(&self, person: &PersonId) -> impl Iterator<Item = &Clue> {
-        self.by_person
-            .get(person)
-            .into_iter()
-            .flatten()
-            .filter_map(|id| self.clues.get(id))
+    /// Get a reference to a clue by it's [`ClueId`].
+    pub fn get(&self, clue: &ClueId) -> Option<&Clue> {
+        self.clues.get(clue)
     }
 
-    /// Get all clues by a [`PersonId`] and a [`LocationId`], also
-    /// takes an option that if set to [`Some`] decides whether to
-    /// only get known or unknown clues
-    pub fn get_by_person_and_location(
-        &self,
-        person: &PersonId,
-        location: &LocationId,
-    ) -> impl Iterator<Item = &Clue> {
-        let people = self.by_person.get(person).into_iter().flatten();
-        let locations: Vec<_> = self
-            .by_location
-            .get(location)
-            .into_iter()
-            .flatten()
-            .collect();
-        people
-            .filter(move |clue| locations.contains(clue))
-            .filter_map(|c| self.clues.get(c))
+    /// Get an [`Iterator`] over every tag used by at least one clue,
+    /// each only once and sorted
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        let mut tags: Vec<_> = self.by_tag.keys().map(String::as_str).collect();
+        tags.sort_unstable();
+        tags.into_iter()
     }
-}
 
-mod assets {
-    use bevy::{
-        asset::{AssetLoader, LoadedAsset},
-        prelude::{AddAsset, Plugin},
-    };
+    /// Count how many clues use each tag
+    pub fn tag_counts(&self) -> HashMap<String, usize> {
+        self.by_tag
+            .iter()
+            .map(|(tag, ids)| (tag.clone(), ids.len()))
+            .collect()
+    }
 
-    use super::{Clues, CluesFile};
+    /// Get an [`Iterator`] over all the clues
+    pub fn iter(&self) -> impl Iterator<Item = &Clue> {
+        self.clues.values()
+    }
 
-    /// Bevy plugin to load a clues file
-    pub struct CluesAssetPlugin;
-    impl Plugin for CluesAssetPlugin {
-        fn build(&self, app: &mut bevy::prelude::App) {
-            app.add_asset::<Clues>().add_asset_loader(CluesAssetLoader);
-        }
+    /// Get an [`Iterator`] over the clues written by an author, see
+    /// [`Clue::author`]
+    pub fn iter_by_author<'a>(&'a self, author: &'a str) -> impl Iterator<Item = &'a Clue> {
+        self.iter()
+            .filter(move |c| c.author.as_deref() == Some(author))
     }
 
-    struct CluesAssetLoader;
+    /// Find the clue whose information is exactly `text`, in any language.
+    /// If several clues match the lowest [`ClueId`] is returned, see
+    /// [`Clues::find_all_by_information`] to get all of them.
+    pub fn find_by_information(&self, text: &str) -> Option<&ClueId> {
+        self.find_all_by_information(text).into_iter().next()
+    }
+
+    /// Find all the clues whose information is exactly `text`, in any
+    /// language, sorted by [`ClueId`]
+    pub fn find_all_by_information(&self, text: &str) -> Vec<&ClueId> {
+        let mut ids: Vec<_> = self
+            .clues
+            .values()
+            .filter(|c| c.information.iter().any(|t| t == text))
+            .map(|c| &c.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Get a reference to a clue by the string of it's [`ClueId`],
+    /// without having to build one.
+    pub fn get_str(&self, clue: &str) -> Option<&Clue> {
+        self.clues.get(clue)
+    }
+
+    /// Get a mutable reference to a clue by it's [`ClueId`]. The clue
+    /// could be changed through the reference, so it counts as modified for
+    /// [`Clues::changed_since`] either way. Use [`Clues::update_clue`] to
+    /// only mark real changes, and to keep the indices up to date.
+    pub fn get_mut(&mut self, clue: &ClueId) -> Option<&mut Clue> {
+        let clue = self.clues.get_mut(clue)?;
+        self.revision += 1;
+        clue.last_modified = self.revision;
+        Some(clue)
+    }
+
+    /// Remove a clue by it's [`ClueId`], returning it if it existed
+    pub fn remove(&mut self, clue: &ClueId) -> Option<Clue> {
+        let clue = self.clues.remove(clue)?;
+        self.revision += 1;
+        self.removed.push((clue.id.clone(), self.revision));
+        self.discovery.remove(&clue.id);
+        for person in &clue.persons {
+            if let Some(peeps) = self.by_person.get_mut(person) {
+                peeps.retain(|id| id != &clue.id);
+                if peeps.is_empty() {
+                    self.by_person.remove(person);
+                }
+            }
+        }
+        for location in &clue.locations {
+            if let Some(locs) = self.by_location.get_mut(location) {
+                locs.retain(|id| id != &clue.id);
+                if locs.is_empty() {
+                    self.by_location.remove(location);
+                }
+            }
+        }
+        for tag in &clue.tags {
+            if let Some(tagged) = self.by_tag.get_mut(tag) {
+                tagged.retain(|id| id != &clue.id);
+                if tagged.is_empty() {
+                    self.by_tag.remove(tag);
+                }
+            }
+        }
+        Some(clue)
+    }
+
+    /// Remove every clue that doesn't match the predicate, keeping all the
+    /// indices up to date like [`Clues::remove`]
+    pub fn retain<F: FnMut(&Clue) -> bool>(&mut self, mut f: F) {
+        let removed: Vec<ClueId> = self
+            .clues
+            .values()
+            .filter(|c| !f(c))
+            .map(|c| c.id.clone())
+            .collect();
+        for id in removed {
+            self.remove(&id);
+        }
+    }
+
+    /// Point every clue referencing the location `from` at `to` instead,
+    /// see [`merge_locations`](crate::locations::merge_locations)
+    pub(crate) fn replace_location(&mut self, from: &LocationId, to: &LocationId) {
+        let ids = self.by_location.get(from).cloned().unwrap_or_default();
+        for id in ids {
+            self.update_clue(&id, |clue| {
+                for location in clue.locations.iter_mut().filter(|l| *l == from) {
+                    *location = to.clone();
+                }
+                let mut seen = HashSet::new();
+                clue.locations.retain(|l| seen.insert(l.clone()));
+            });
+        }
+    }
+
+    /// Edit a clue in place, keeping the person, location and tag indices
+    /// up to date, unlike [`Clues::get_mut`]. The clue's id can't be
+    /// changed, any change to it is undone. Returns `false` if there is no
+    /// such clue.
+    pub fn update_clue<F: FnOnce(&mut Clue)>(&mut self, id: &ClueId, f: F) -> bool {
+        let mut clue = match self.clues.get(id) {
+            Some(clue) => clue.clone(),
+            None => return false,
+        };
+        f(&mut clue);
+        clue.id = id.clone();
+        if clue.changed_fields(&self.clues[id]).is_empty() {
+            return true;
+        }
+        // Re-inserting keeps the indices right, but would forget how
+        // the clue was discovered
+        let discovery = self.discovery.remove(id);
+        self.remove(id);
+        self.insert(clue);
+        if let Some(discovery) = discovery {
+            self.discovery.insert(id.clone(), discovery);
+        }
+        true
+    }
+
+    /// Set whether a clue is known, returns `true` if this changed
+    /// the clue. Making a clue unknown forgets its [`DiscoverySource`].
+    pub fn set_known(&mut self, clue: &ClueId, known: bool) -> bool {
+        if !known {
+            self.discovery.remove(clue);
+        }
+        match self.clues.get_mut(clue) {
+            Some(clue) if clue.known != known => {
+                self.revision += 1;
+                clue.known = known;
+                clue.last_modified = self.revision;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Mark all the listed clues as known, skipping ids that aren't clues.
+    /// Returns how many clues weren't known before.
+    pub fn set_known_bulk(&mut self, ids: &[ClueId]) -> usize {
+        ids.iter().filter(|id| self.set_known(id, true)).count()
+    }
+
+    /// Get the `known` flag of every clue, to be restored later with
+    /// [`Clues::apply_known_snapshot`]
+    pub fn known_snapshot(&self) -> HashMap<ClueId, bool> {
+        self.clues
+            .iter()
+            .map(|(id, clue)| (id.clone(), clue.known))
+            .collect()
+    }
+
+    /// Restore the `known` flags saved with [`Clues::known_snapshot`].
+    /// Clues missing from the snapshot, and ids in the snapshot that
+    /// aren't clues, are left alone.
+    pub fn apply_known_snapshot(&mut self, snapshot: &HashMap<ClueId, bool>) {
+        for (id, known) in snapshot {
+            self.set_known(id, *known);
+        }
+    }
+
+    /// Mark a clue as known and record how it was discovered,
+    /// returns `false` if there is no such clue
+    pub fn reveal_with_source(&mut self, clue: &ClueId, source: DiscoverySource) -> bool {
+        if !self.clues.contains_key(clue) {
+            return false;
+        }
+        self.set_known(clue, true);
+        self.discovery.insert(clue.clone(), source);
+        true
+    }
+
+    /// Get how a clue was discovered, if it was revealed with
+    /// [`Clues::reveal_with_source`]
+    pub fn discovery_source(&self, clue: &ClueId) -> Option<&DiscoverySource> {
+        self.discovery.get(clue)
+    }
+
+    /// The current revision, this increases every time a clue
+    /// is inserted, removed or modified
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Get all clues modified after the given revision. Clues removed
+    /// since then are listed by [`Clues::removed_since`] instead.
+    pub fn changed_since(&self, revision: u64) -> impl Iterator<Item = &Clue> {
+        self.clues
+            .values()
+            .filter(move |c| c.last_modified > revision)
+    }
+
+    /// Get the ids of the clues removed after the given revision, that
+    /// haven't been inserted again since, oldest removal first
+    pub fn removed_since(&self, revision: u64) -> impl Iterator<Item = &ClueId> {
+        self.removed
+            .iter()
+            .filter(move |(_, removed_at)| *removed_at > revision)
+            .map(|(id, _)| id)
+    }
+
+    /// Get all clues by a [`LocationId`]
+    pub fn get_by_location(&self, location: &LocationId) -> impl Iterator<Item = &Clue> {
+        self.by_location
+            .get(location)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.clues.get(id))
+    }
+
+    /// Get all clues with a tag
+    pub fn get_by_tag(&self, tag: &str) -> impl Iterator<Item = &Clue> {
+        self.by_tag
+            .get(tag)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.clues.get(id))
+    }
+
+    /// Get all clues by a [`PersonId`], also takes an option
+    /// that if set to [`Some`] decides whether to only get known
+    /// or unknown clues
+    pub fn get_by_person(&self, person: &PersonId) -> impl Iterator<Item = &Clue> {
+        self.by_person
+            .get(person)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.clues.get(id))
+    }
+
+    /// Get the person's clues that are only at locations missing from
+    /// `known_locs`, e.g. the locations the players have discovered.
+    /// Clues without any locations aren't at an unknown location, so they
+    /// are left out.
+    pub fn person_clues_at_unknown_locations<'a>(
+        &'a self,
+        person: &PersonId,
+        known_locs: &'a HashSet<LocationId>,
+    ) -> impl Iterator<Item = &'a Clue> {
+        self.get_by_person(person).filter(|c| {
+            !c.locations.is_empty() && !c.locations.iter().any(|l| known_locs.contains(l))
+        })
+    }
+
+    /// Get the clues linked to no one but the person, the ones that would
+    /// be left without persons if it was deleted. Sorted by id.
+    pub fn clues_only_referencing_person(&self, person: &PersonId) -> Vec<&Clue> {
+        let mut clues: Vec<_> = self
+            .get_by_person(person)
+            .filter(|c| c.persons.iter().all(|p| p == person))
+            .collect();
+        clues.sort_by(|a, b| a.id.cmp(&b.id));
+        clues.dedup_by(|a, b| a.id == b.id);
+        clues
+    }
+
+    /// Get the clues linked to any of the persons, each paired with the
+    /// person that matched it. A clue linked to several of the persons is
+    /// returned once for each of them.
+    pub fn get_by_persons_tagged<'a>(
+        &'a self,
+        persons: &'a [PersonId],
+    ) -> impl Iterator<Item = (&'a Clue, &'a PersonId)> {
+        persons
+            .iter()
+            .flat_map(move |person| self.get_by_person(person).map(move |c| (c, person)))
+    }
+
+    /// Get all clues by a [`PersonId`] and a [`LocationId`], also
+    /// takes an option that if set to [`Some`] decides whether to
+    /// only get known or unknown clues
+    pub fn get_by_person_and_location(
+        &self,
+        person: &PersonId,
+        location: &LocationId,
+    ) -> impl Iterator<Item = &Clue> {
+        #[cfg(feature = "trace")]
+        let span = crate::trace::QuerySpan::start("get_by_person_and_location");
+        let people = self.by_person.get(person).into_iter().flatten();
+        let locations: HashSet<&ClueId> = self
+            .by_location
+            .get(location)
+            .into_iter()
+            .flatten()
+            .collect();
+        let clues = people
+            .filter(move |id: &&ClueId| locations.contains(*id))
+            .filter_map(|c| self.clues.get(c));
+        #[cfg(feature = "trace")]
+        let clues = span.finish(clues);
+        clues
+    }
+
+    /// Get all clues at any of the locations a [`PersonId`] is linked to
+    /// through their own clues, each clue is only returned once
+    pub fn get_for_person_locations<'a>(
+        &'a self,
+        person: &PersonId,
+    ) -> impl Iterator<Item = &'a Clue> {
+        let locations: HashSet<&LocationId> = self
+            .get_by_person(person)
+            .flat_map(|c| &c.locations)
+            .collect();
+        let mut seen = HashSet::new();
+        locations
+            .into_iter()
+            .flat_map(|l| self.by_location.get(l).into_iter().flatten())
+            .filter(move |id| seen.insert(*id))
+            .filter_map(|id| self.clues.get(id))
+    }
+
+    /// Get all clues at a [`LocationId`] or any of its ancestors, each
+    /// clue is only returned once
+    pub fn get_at_or_above<'a>(
+        &'a self,
+        locations: &'a Locations,
+        location: &LocationId,
+    ) -> impl Iterator<Item = &'a Clue> {
+        let mut seen = HashSet::new();
+        let clues: Vec<_> = std::iter::once(location)
+            .chain(locations.iter_ancestors(location).map(|l| &l.id))
+            .flat_map(|l| self.by_location.get(l).into_iter().flatten())
+            .filter(|id| seen.insert(*id))
+            .filter_map(|id| self.clues.get(id))
+            .collect();
+        clues.into_iter()
+    }
+
+    /// Group the clues of a [`PersonId`] by the locations they reference.
+    /// A clue linked to several locations appears under each of them,
+    /// clues without any locations are left out.
+    pub fn person_clues_by_location<'a>(
+        &'a self,
+        person: &PersonId,
+    ) -> HashMap<LocationId, Vec<&'a Clue>> {
+        let mut grouped: HashMap<LocationId, Vec<&Clue>> = HashMap::new();
+        for clue in self.get_by_person(person) {
+            for location in &clue.locations {
+                grouped.entry(location.clone()).or_default().push(clue);
+            }
+        }
+        grouped
+    }
+
+    /// Get all clues at a [`LocationId`] or any of its descendants, each
+    /// clue is only returned once
+    pub fn get_in_location_tree<'a>(
+        &'a self,
+        locations: &Locations,
+        location: &LocationId,
+    ) -> impl Iterator<Item = &'a Clue> {
+        #[cfg(feature = "trace")]
+        let span = crate::trace::QuerySpan::start("get_in_location_tree");
+        let clues = self.get_in_tree(location, &locations.descendant_ids(location));
+        #[cfg(feature = "trace")]
+        let clues = span.finish(clues);
+        clues
+    }
+
+    /// Count the distinct locations a clue touches, its own locations and
+    /// all their descendants. Returns 0 if there is no such clue.
+    pub fn clue_reach(&self, locations: &Locations, id: &ClueId) -> usize {
+        let mut reach = HashSet::new();
+        for location in self.clues.get(id).into_iter().flat_map(|c| &c.locations) {
+            reach.insert(location.clone());
+            reach.extend(locations.descendant_ids(location));
+        }
+        reach.len()
+    }
+
+    /// Same as [`Clues::get_in_location_tree`], but with the descendants
+    /// already computed, e.g. from a [`crate::locations::LocationTreeCache`]
+    pub fn get_in_tree<'a>(
+        &'a self,
+        location: &LocationId,
+        descendants: &HashSet<LocationId>,
+    ) -> impl Iterator<Item = &'a Clue> {
+        let mut seen = HashSet::new();
+        let clues: Vec<_> = std::iter::once(location)
+            .chain(descendants)
+            .flat_map(|l| self.by_location.get(l).into_iter().flatten())
+            .filter(|id| seen.insert(*id))
+            .filter_map(|id| self.clues.get(id))
+            .collect();
+        clues.into_iter()
+    }
+
+    /// Get the `n` most important clues, clues of equal importance
+    /// are ordered by their [`ClueId`]
+    pub fn top(&self, n: usize, exclude_red_herrings: bool) -> Vec<&Clue> {
+        let mut clues: Vec<_> = self
+            .clues
+            .values()
+            .filter(|c| !(exclude_red_herrings && c.red_herring))
+            .collect();
+        clues.sort_by(|a, b| {
+            b.importance
+                .cmp(&a.importance)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        clues.truncate(n);
+        clues
+    }
+
+    /// Get the clues with a [`Clue::time`] between `start` and `end`,
+    /// inclusive. Clues without a time are left out.
+    pub fn between(&self, start: i64, end: i64) -> impl Iterator<Item = &Clue> {
+        self.clues
+            .values()
+            .filter(move |c| c.time.is_some_and(|t| start <= t && t <= end))
+    }
+
+    /// Suggest persons to add to a clue alongside `seed`, ranked by how
+    /// many clues they share with any of the seed persons, ties broken by
+    /// [`PersonId`]. At most `limit` persons are returned and none of the
+    /// seed persons are.
+    pub fn suggest_persons(&self, seed: &[PersonId], limit: usize) -> Vec<PersonId> {
+        let mut seen = HashSet::new();
+        let mut counts: HashMap<&PersonId, usize> = HashMap::new();
+        for clue in seed
+            .iter()
+            .flat_map(|p| self.get_by_person(p))
+            .filter(|c| seen.insert(&c.id))
+        {
+            for person in clue.persons.iter().filter(|p| !seed.contains(p)) {
+                *counts.entry(person).or_default() += 1;
+            }
+        }
+        let mut suggestions: Vec<_> = counts.into_iter().collect();
+        suggestions
+            .sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        suggestions
+            .into_iter()
+            .take(limit)
+            .map(|(p, _)| p.clone())
+            .collect()
+    }
+
+    /// Get all the clues sorted by how many persons they involve, most
+    /// first, with ties ordered by [`ClueId`]
+    pub fn by_person_count_desc(&self) -> Vec<&Clue> {
+        let mut clues: Vec<_> = self.clues.values().collect();
+        clues.sort_by(|a, b| {
+            b.persons
+                .len()
+                .cmp(&a.persons.len())
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        clues
+    }
+
+    /// Pick a small set of clues that between them mention every person
+    /// linked to any clue, e.g. for a summary.
+    ///
+    /// This is a greedy heuristic, it repeatedly takes the clue mentioning
+    /// the most persons not yet covered, with ties going to the lowest
+    /// [`ClueId`]. The result usually isn't far off, but isn't guaranteed
+    /// to be the smallest such set. Clues are returned in the order picked.
+    pub fn min_clues_covering_persons(&self) -> Vec<&Clue> {
+        let mut uncovered: HashSet<&PersonId> = self.by_person.keys().collect();
+        let mut candidates: Vec<_> = self.clues.values().collect();
+        candidates.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut picked = Vec::new();
+        while !uncovered.is_empty() {
+            let newly_covered = |c: &Clue| {
+                c.persons
+                    .iter()
+                    .filter(|p| uncovered.contains(p))
+                    .collect::<HashSet<_>>()
+                    .len()
+            };
+            // `max_by_key` keeps the last maximum, so search from the end to
+            // keep the lowest id on ties
+            let best = match candidates
+                .iter()
+                .rev()
+                .map(|c| (newly_covered(c), *c))
+                .max_by_key(|(count, _)| *count)
+            {
+                Some((count, clue)) if count > 0 => clue,
+                _ => break,
+            };
+            for person in &best.persons {
+                uncovered.remove(person);
+            }
+            picked.push(best);
+        }
+        picked
+    }
+
+    /// Get the clues that aren't known yet, but could be found next
+    /// as all the clues they require are known
+    pub fn frontier<'a>(
+        &'a self,
+        known: &'a CluesComponent,
+        exclude_red_herrings: bool,
+    ) -> impl Iterator<Item = &'a Clue> {
+        self.clues.values().filter(move |c| {
+            !known.contains(&c.id)
+                && !(exclude_red_herrings && c.red_herring)
+                && c.requires.iter().all(|r| known.contains(r))
+        })
+    }
+
+    /// Get the persons mentioned by any of the known clues, each only once
+    /// and sorted by their [`PersonId`]
+    pub fn known_persons<'a>(
+        &'a self,
+        known: &'a CluesComponent,
+    ) -> impl Iterator<Item = &'a PersonId> {
+        let mut persons: Vec<_> = known
+            .iter()
+            .filter_map(|id| self.clues.get(id))
+            .flat_map(|c| &c.persons)
+            .collect();
+        persons.sort();
+        persons.dedup();
+        persons.into_iter()
+    }
+
+    /// Get the locations mentioned by any of the known clues, each only
+    /// once and sorted by their [`LocationId`]
+    pub fn known_locations<'a>(
+        &'a self,
+        known: &'a CluesComponent,
+    ) -> impl Iterator<Item = &'a LocationId> {
+        let mut locations: Vec<_> = known
+            .iter()
+            .filter_map(|id| self.clues.get(id))
+            .flat_map(|c| &c.locations)
+            .collect();
+        locations.sort();
+        locations.dedup();
+        locations.into_iter()
+    }
+
+    /// Split the clues into those that are known and those that aren't,
+    /// in one pass
+    pub fn partition_known<'a>(
+        &'a self,
+        known: &'a CluesComponent,
+    ) -> (Vec<&'a Clue>, Vec<&'a Clue>) {
+        self.clues.values().partition(|c| known.contains(&c.id))
+    }
+
+    /// Get the clues that are known even though some of the clues they
+    /// require aren't, e.g. after they were revealed by hand
+    pub fn inconsistent_known<'a>(
+        &'a self,
+        known: &'a CluesComponent,
+    ) -> impl Iterator<Item = &'a Clue> {
+        self.clues
+            .values()
+            .filter(move |c| known.contains(&c.id) && c.requires.iter().any(|r| !known.contains(r)))
+    }
+
+    /// Get the pairs of known clues that contradict each other, as declared
+    /// by [`Clue::contradicts`] on either of them. Each pair is listed once
+    /// with the lower [`ClueId`] first, and the pairs are sorted.
+    pub fn active_contradictions(&self, known: &CluesComponent) -> Vec<(ClueId, ClueId)> {
+        let mut contradictions: Vec<_> = known
+            .iter()
+            .filter_map(|id| self.clues.get(id))
+            .flat_map(|clue| {
+                clue.contradicts
+                    .iter()
+                    .filter(|other| known.contains(other) && *other != &clue.id)
+                    .map(|other| {
+                        if clue.id < *other {
+                            (clue.id.clone(), other.clone())
+                        } else {
+                            (other.clone(), clue.id.clone())
+                        }
+                    })
+            })
+            .collect();
+        contradictions.sort();
+        contradictions.dedup();
+        contradictions
+    }
+
+    /// Count how many layers of [`Clue::requires`] have to be found before
+    /// a clue can be. Clues on the [`Clues::frontier`] are 0, as are known
+    /// clues, a clue requiring a frontier clue is 1 and so on. Returns
+    /// [`None`] if the clue, or one it needs, doesn't exist or is in a
+    /// cycle so can never be found.
+    pub fn reveal_distance(&self, id: &ClueId, known: &CluesComponent) -> Option<usize> {
+        fn distance<'a>(
+            clues: &'a Clues,
+            id: &'a ClueId,
+            known: &CluesComponent,
+            visiting: &mut HashSet<&'a ClueId>,
+            done: &mut HashMap<&'a ClueId, Option<usize>>,
+        ) -> Option<usize> {
+            if known.contains(id) {
+                return Some(0);
+            }
+            if let Some(d) = done.get(id) {
+                return *d;
+            }
+            if !visiting.insert(id) {
+                // Requires itself somewhere along the way
+                return None;
+            }
+            let mut result = clues.clues.get(id).map(|_| 0);
+            for required in clues.clues.get(id).into_iter().flat_map(|c| &c.requires) {
+                if known.contains(required) {
+                    continue;
+                }
+                result = match (result, distance(clues, required, known, visiting, done)) {
+                    (Some(d), Some(r)) => Some(d.max(r + 1)),
+                    _ => None,
+                };
+            }
+            visiting.remove(id);
+            done.insert(id, result);
+            result
+        }
+        distance(self, id, known, &mut HashSet::new(), &mut HashMap::new())
+    }
+
+    /// Get all the clues matching a [`ClueFilter`]
+    pub fn query_expr<'a>(&'a self, filter: &ClueFilter) -> impl Iterator<Item = &'a Clue> {
+        let clues: Vec<_> = self.clues.values().filter(|c| filter.matches(c)).collect();
+        clues.into_iter()
+    }
+
+    /// Get a page of the clues matching a [`ClueQuery`]. The matching
+    /// clues are ordered by their [`ClueId`], then `limit` of them are
+    /// returned starting from `offset`.
+    pub fn page(&self, query: ClueQuery, offset: usize, limit: usize) -> Vec<&Clue> {
+        let mut clues: Vec<_> = match &query {
+            ClueQuery::All => self.clues.values().collect(),
+            ClueQuery::ByPerson(person) => self.get_by_person(person).collect(),
+            ClueQuery::ByLocation(location) => self.get_by_location(location).collect(),
+            ClueQuery::ByPersonAndLocation(person, location) => {
+                self.get_by_person_and_location(person, location).collect()
+            }
+            ClueQuery::Matching(query) => {
+                self.clues.values().filter(|c| query.matches(c)).collect()
+            }
+        };
+        clues.sort_by(|a, b| a.id.cmp(&b.id));
+        clues.dedup_by(|a, b| a.id == b.id);
+        clues.into_iter().skip(offset).take(limit).collect()
+    }
+
+    /// Get the ids of all the clues within `max_hops` steps along
+    /// [`Clue::related_clues`] from a clue, nearest first, not including
+    /// the clue itself
+    pub fn related_within(&self, start: &ClueId, max_hops: usize) -> Vec<ClueId> {
+        let mut related = Vec::new();
+        let mut seen = HashSet::new();
+        seen.insert(start);
+        let mut queue = VecDeque::new();
+        queue.push_back((start, 0));
+        while let Some((id, hops)) = queue.pop_front() {
+            if hops == max_hops {
+                continue;
+            }
+            for next in self
+                .clues
+                .get(id)
+                .into_iter()
+                .flat_map(|c| &c.related_clues)
+            {
+                if seen.insert(next) {
+                    related.push(next.clone());
+                    queue.push_back((next, hops + 1));
+                }
+            }
+        }
+        related
+    }
+
+    /// Get the whole investigation thread a clue is part of, every clue
+    /// connected to it through [`Clue::related_clues`] in either direction,
+    /// as a new [`Clues`] with its own indices. The result is empty if
+    /// there is no such clue.
+    pub fn investigation_component(&self, start: &ClueId) -> Clues {
+        let mut links: HashMap<&ClueId, Vec<&ClueId>> = HashMap::new();
+        for clue in self.clues.values() {
+            for related in clue
+                .related_clues
+                .iter()
+                .filter(|r| self.clues.contains_key(*r))
+            {
+                links.entry(&clue.id).or_default().push(related);
+                links.entry(related).or_default().push(&clue.id);
+            }
+        }
+
+        let mut component = Clues::new();
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<&ClueId> = self
+            .clues
+            .get_key_value(start)
+            .map(|(id, _)| id)
+            .into_iter()
+            .collect();
+        while let Some(id) = queue.pop_front() {
+            if !seen.insert(id) {
+                continue;
+            }
+            component.insert(self.clues[id].clone());
+            if let Some(source) = self.discovery.get(id) {
+                component.discovery.insert(id.clone(), source.clone());
+            }
+            queue.extend(links.get(id).into_iter().flatten());
+        }
+        component
+    }
+
+    /// Find the shortest chain of [`Clue::related_clues`] from one clue to
+    /// another, including both ends. If `directed` isn't set the links can
+    /// be followed in either direction. Returns [`None`] if either clue
+    /// doesn't exist or they aren't connected.
+    pub fn relation_path(&self, from: &ClueId, to: &ClueId, directed: bool) -> Option<Vec<ClueId>> {
+        self.clues.get(from)?;
+        self.clues.get(to)?;
+
+        let mut links: HashMap<&ClueId, Vec<&ClueId>> = HashMap::new();
+        for clue in self.clues.values() {
+            for related in clue
+                .related_clues
+                .iter()
+                .filter(|r| self.clues.contains_key(*r))
+            {
+                links.entry(&clue.id).or_default().push(related);
+                if !directed {
+                    links.entry(related).or_default().push(&clue.id);
+                }
+            }
+        }
+
+        let mut previous: HashMap<&ClueId, &ClueId> = HashMap::new();
+        let mut seen = HashSet::new();
+        seen.insert(from);
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(id) = queue.pop_front() {
+            if id == to {
+                let mut path = vec![id.clone()];
+                let mut current = id;
+                while let Some(prev) = previous.get(current) {
+                    path.push((*prev).clone());
+                    current = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in links.get(id).into_iter().flatten() {
+                if seen.insert(*next) {
+                    previous.insert(next, id);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Write the clues out as markdown, for a wiki. Each clue gets a
+    /// section headed by its id, with its information followed by lists
+    /// of the persons and locations linked to it. Clues are ordered by
+    /// id, and if `known_only` is set unknown clues are left out.
+    ///
+    /// The information is in the [`DEFAULT_LANGUAGE`], or, for clues that
+    /// haven't got it in that language, the [`LocalizedText::first`] one.
+    pub fn to_markdown(&self, known_only: bool) -> String {
+        let mut clues: Vec<_> = self
+            .clues
+            .values()
+            .filter(|c| !known_only || c.known)
+            .collect();
+        clues.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut markdown = String::new();
+        for clue in clues {
+            markdown.push_str(&format!(
+                "## {}\n\n{}\n",
+                clue.id.0,
+                clue.information_in(DEFAULT_LANGUAGE)
+                    .or_else(|| clue.information.first())
+                    .unwrap_or_default()
+            ));
+            if !clue.persons.is_empty() {
+                markdown.push_str("\n### Persons\n\n");
+                for person in &clue.persons {
+                    markdown.push_str(&format!("- {}\n", person.0));
+                }
+            }
+            if !clue.locations.is_empty() {
+                markdown.push_str("\n### Locations\n\n");
+                for location in &clue.locations {
+                    let location: &str = location.borrow();
+                    markdown.push_str(&format!("- {}\n", location));
+                }
+            }
+            markdown.push('\n');
+        }
+        markdown
+    }
+
+    /// Write out the person, location and tag indices in a readable form,
+    /// to help debug unexpected query results
+    pub fn dump_indices(&self) -> String {
+        fn dump<K: Borrow<str>>(name: &str, index: &HashMap<K, Vec<ClueId>>) -> String {
+            let mut entries: Vec<_> = index
+                .iter()
+                .map(|(key, ids)| {
+                    let ids: Vec<&str> = ids.iter().map(|id| id.0.as_str()).collect();
+                    format!("  {}: {}\n", key.borrow(), ids.join(", "))
+                })
+                .collect();
+            entries.sort();
+            format!("{}:\n{}", name, entries.concat())
+        }
+
+        [
+            dump("by_person", &self.by_person),
+            dump("by_location", &self.by_location),
+            dump("by_tag", &self.by_tag),
+        ]
+        .concat()
+    }
+
+    /// Get all clues relevant at an in-game time, see [`Clue::is_active_at`]
+    pub fn active_at(&self, time: i64) -> impl Iterator<Item = &Clue> {
+        self.clues.values().filter(move |c| c.is_active_at(time))
+    }
+
+    /// Get the location with the most clues and how many it has, ties
+    /// go to the smallest [`LocationId`]
+    pub fn busiest_location(&self) -> Option<(&LocationId, usize)> {
+        self.by_location
+            .iter()
+            .map(|(id, clues)| (id, clues.len()))
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+    }
+
+    /// Get every person linked to a clue, along with the other persons
+    /// they share a clue with. Persons only ever mentioned on their own
+    /// have no neighbours.
+    pub fn person_graph(&self) -> HashMap<&PersonId, HashSet<&PersonId>> {
+        let mut graph: HashMap<&PersonId, HashSet<&PersonId>> = HashMap::new();
+        for clue in self.clues.values() {
+            for person in &clue.persons {
+                graph
+                    .entry(person)
+                    .or_default()
+                    .extend(clue.persons.iter().filter(|p| *p != person));
+            }
+        }
+        graph
+    }
+
+    /// Get the person sharing clues with the most other persons in the
+    /// [`Clues::person_graph`], and how many they share clues with. Ties
+    /// go to the lowest [`PersonId`]. [`None`] if no clue has any persons.
+    pub fn most_connected_person(&self) -> Option<(&PersonId, usize)> {
+        self.person_graph()
+            .into_iter()
+            .map(|(id, neighbours)| (id, neighbours.len()))
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+    }
+
+    /// Get pairs of location ids used by clues that only differ by case,
+    /// e.g. `Docks` and `docks`, which probably should be the same
+    pub fn casing_conflicts(&self) -> Vec<(LocationId, LocationId)> {
+        casing_conflicts(self.by_location.keys())
+    }
+
+    /// Same as [`Clues::casing_conflicts`], but for person ids
+    pub fn person_casing_conflicts(&self) -> Vec<(PersonId, PersonId)> {
+        casing_conflicts(self.by_person.keys())
+    }
+
+    /// Collect the clues, locations and associates of a person into a
+    /// [`PersonDossier`], resolving location names with `locations`
+    pub fn person_dossier(&self, person: &PersonId, locations: &Locations) -> PersonDossier {
+        let mut clues: Vec<_> = self.get_by_person(person).collect();
+        clues.sort_by(|a, b| a.id.cmp(&b.id));
+        clues.dedup_by(|a, b| a.id == b.id);
+
+        let mut location_ids: Vec<&LocationId> = clues.iter().flat_map(|c| &c.locations).collect();
+        location_ids.sort();
+        location_ids.dedup();
+
+        let mut associates: Vec<&PersonId> = clues
+            .iter()
+            .flat_map(|c| &c.persons)
+            .filter(|p| *p != person)
+            .collect();
+        associates.sort();
+        associates.dedup();
+
+        PersonDossier {
+            person: person.clone(),
+            clues: clues
+                .into_iter()
+                .map(|c| DossierClue {
+                    id: c.id.clone(),
+                    information: c.information.clone(),
+                    known: c.known,
+                })
+                .collect(),
+            locations: location_ids
+                .into_iter()
+                .map(|id| DossierLocation {
+                    id: id.clone(),
+                    name: locations.get(id).map(|l| l.name.clone()),
+                })
+                .collect(),
+            associates: associates.into_iter().cloned().collect(),
+        }
+    }
+
+    /// Get the clues whose locations are on more than one map. This is
+    /// sometimes intended, so it's only something to check, not an error.
+    /// Locations that don't exist or aren't on a map are ignored.
+    pub fn cross_map_clues<'a>(
+        &'a self,
+        locations: &'a Locations,
+    ) -> impl Iterator<Item = &'a Clue> {
+        self.clues.values().filter(move |c| {
+            let mut maps = c
+                .locations
+                .iter()
+                .filter_map(|l| locations.get(l)?.map.as_ref());
+            maps.next()
+                .is_some_and(|first| maps.any(|other| other != first))
+        })
+    }
+
+    /// Get the maps a clue's locations are on, i.e. the maps that need a
+    /// pin for the clue. Locations that don't exist or aren't on a map
+    /// are ignored.
+    pub fn clue_maps(&self, locations: &Locations, id: &ClueId) -> HashSet<MapId> {
+        self.clues
+            .get(id)
+            .into_iter()
+            .flat_map(|c| &c.locations)
+            .filter_map(|l| locations.get(l)?.map.clone())
+            .collect()
+    }
+
+    /// Find the pairs of clue ids within `max_distance` edits of each other,
+    /// which are likely typos of the same id. Each pair and the list of
+    /// pairs are sorted.
+    pub fn near_duplicate_ids(&self, max_distance: usize) -> Vec<(ClueId, ClueId)> {
+        let mut ids: Vec<_> = self.clues.keys().collect();
+        ids.sort();
+        let mut pairs = Vec::new();
+        for (i, a) in ids.iter().enumerate() {
+            for b in &ids[i + 1..] {
+                if edit_distance(&a.0, &b.0) <= max_distance {
+                    pairs.push(((*a).clone(), (*b).clone()));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Compare these clues with another version of them, e.g. after
+    /// someone else edited the file. Every list in the [`ClueDiff`] is
+    /// sorted by id.
+    pub fn diff(&self, other: &Clues) -> ClueDiff {
+        let mut diff = ClueDiff::default();
+        for (id, clue) in &self.clues {
+            match other.get(id) {
+                Some(new) => {
+                    let fields = clue.changed_fields(new);
+                    if !fields.is_empty() {
+                        diff.changed.push(ClueChange {
+                            id: id.clone(),
+                            fields,
+                        });
+                    }
+                }
+                None => diff.removed.push(id.clone()),
+            }
+        }
+        diff.added = other
+            .clues
+            .keys()
+            .filter(|id| !self.clues.contains_key(*id))
+            .cloned()
+            .collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort_by(|a, b| a.id.cmp(&b.id));
+        diff
+    }
+
+    /// Hash every clue, so two copies of the clues can be checked to be the
+    /// same, e.g. whether a client is up to date. Equal clues always give
+    /// the same hash whatever order they were inserted in, and the hash
+    /// doesn't change between builds. [`Clue::last_modified`] isn't
+    /// included.
+    pub fn content_hash(&self) -> u64 {
+        let mut clues: Vec<_> = self.clues.values().collect();
+        clues.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut hash = Fnv1a::new();
+        hash.write_u64(clues.len() as u64);
+        for clue in clues {
+            hash.write_str(&clue.id.0);
+            hash.write_strs(clue.locations.iter().map(|l| l.borrow()));
+            hash.write_strs(clue.persons.iter().map(|p| p.borrow()));
+            match &clue.information {
+                LocalizedText::Plain(text) => {
+                    hash.write(&[0]);
+                    hash.write_str(text);
+                }
+                LocalizedText::Localized(texts) => {
+                    hash.write(&[1]);
+                    hash.write_map(texts);
+                }
+            }
+            hash.write(&[clue.known as u8, clue.importance, clue.red_herring as u8]);
+            hash.write_strs(clue.requires.iter().map(|c| c.borrow()));
+            hash.write_strs(clue.tags.iter().map(String::as_str));
+            hash.write_strs(clue.related_clues.iter().map(|c| c.borrow()));
+            hash.write_strs(clue.contradicts.iter().map(|c| c.borrow()));
+            hash.write_map(&clue.metadata);
+            hash.write_option(clue.time);
+            hash.write_option(clue.valid_from);
+            hash.write_option(clue.valid_until);
+            match &clue.author {
+                Some(author) => {
+                    hash.write(&[1]);
+                    hash.write_str(author);
+                }
+                None => hash.write(&[0]),
+            }
+        }
+        hash.finish()
+    }
+
+    /// Get all clues that have no persons and no locations, these
+    /// can't be found by any of the other lookups
+    pub fn unreachable(&self) -> impl Iterator<Item = &Clue> {
+        self.clues
+            .values()
+            .filter(|c| c.persons.is_empty() && c.locations.is_empty())
+    }
+}
+
+/// A read-only [`Clues`] that is cheap to clone and share between
+/// systems, made with [`Clues::freeze`]. All the query methods of
+/// [`Clues`] are available through [`Deref`].
+#[derive(Debug, Clone)]
+pub struct FrozenClues(Arc<Clues>);
+
+impl FrozenClues {
+    /// Check whether two frozen collections share the same storage
+    pub fn ptr_eq(&self, other: &FrozenClues) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for FrozenClues {
+    type Target = Clues;
+
+    fn deref(&self) -> &Clues {
+        &self.0
+    }
+}
+
+/// The FNV-1a hash, used wherever a hash has to stay the same between
+/// builds and platforms, which [`std::hash::Hash`] doesn't promise
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// Write a string followed by a `0`, so `"ab", "c"` and `"a", "bc"`
+    /// hash differently
+    fn write_str(&mut self, text: &str) {
+        self.write(text.as_bytes());
+        self.write(&[0]);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_option(&mut self, n: Option<i64>) {
+        match n {
+            Some(n) => {
+                self.write(&[1]);
+                self.write(&n.to_le_bytes());
+            }
+            None => self.write(&[0]),
+        }
+    }
+
+    /// Write a list of strings, prefixed with its length
+    fn write_strs<'a>(&mut self, texts: impl ExactSizeIterator<Item = &'a str>) {
+        self.write_u64(texts.len() as u64);
+        for text in texts {
+            self.write_str(text);
+        }
+    }
+
+    /// Write key value pairs sorted by key, so the order of a [`HashMap`]
+    /// doesn't matter
+    fn write_map(&mut self, map: &HashMap<String, String>) {
+        let mut pairs: Vec<_> = map.iter().collect();
+        pairs.sort();
+        self.write_u64(pairs.len() as u64);
+        for (key, value) in pairs {
+            self.write_str(key);
+            self.write_str(value);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Find all the pairs of ids that are the same ignoring case, each pair
+/// and the list of pairs are sorted
+fn casing_conflicts<'a, K>(ids: impl Iterator<Item = &'a K>) -> Vec<(K, K)>
+where
+    K: Borrow<str> + Ord + Clone + 'a,
+{
+    let mut by_lowercase: HashMap<String, Vec<&K>> = HashMap::new();
+    for id in ids {
+        by_lowercase
+            .entry(id.borrow().to_lowercase())
+            .or_default()
+            .push(id);
+    }
+    let mut conflicts = Vec::new();
+    for mut ids in by_lowercase.into_values() {
+        ids.sort();
+        for (i, a) in ids.iter().enumerate() {
+            for b in &ids[i + 1..] {
+                conflicts.push(((*a).clone(), (*b).clone()));
+            }
+        }
+    }
+    conflicts.sort();
+    conflicts
+}
+
+/// Event to log the indices of all the loaded [`Clues`], this only
+/// does anything when the [`Mode`] resource is [`Mode::DM`]
+#[derive(Debug)]
+pub struct DumpIndices;
+
+fn dump_indices(
+    mut events: EventReader<DumpIndices>,
+    mode: Option<Res<Mode>>,
+    assets: Res<Assets<Clues>>,
+) {
+    if events.iter().count() == 0 || !matches!(mode.as_deref(), Some(Mode::DM)) {
+        return;
+    }
+    for (handle, clues) in assets.iter() {
+        info!("Clue indices for {:?}:\n{}", handle, clues.dump_indices());
+    }
+}
+
+mod assets {
+    use bevy::{
+        asset::{AssetLoader, LoadedAsset},
+        prelude::{AddAsset, Plugin},
+    };
+
+    use super::{
+        dump_indices, yaml, Clue, ClueId, Clues, CluesFile, DumpIndices, IdStrategy, LocalizedText,
+        PersonId,
+    };
+    use crate::{extensions::AssetExtensions, validation::TextValidation};
+
+    /// Bevy plugin to load a clues file
+    pub struct CluesAssetPlugin {
+        extensions: AssetExtensions,
+        text_validation: Option<TextValidation>,
+        id_strategy: IdStrategy,
+    }
+
+    impl CluesAssetPlugin {
+        /// Create the plugin, loading clues from files with any of the
+        /// given extensions, see [`CluesAssetPlugin::with_extensions`]
+        pub fn new(extensions: Vec<String>) -> Self {
+            Self::default().with_extensions(extensions)
+        }
+
+        /// Load clues from files with any of the given extensions instead
+        /// of the default `clues.yml`. Extensions are given without a
+        /// leading `.`, e.g. `clue.yaml`.
+        /// An empty list keeps the default.
+        pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+            self.extensions.set(extensions);
+            self
+        }
+
+        /// Set how ids are generated for clues loaded without one,
+        /// [`IdStrategy::Sequential`] by default
+        pub fn with_id_strategy(mut self, strategy: IdStrategy) -> Self {
+            self.id_strategy = strategy;
+            self
+        }
+
+        /// Warn about suspicious [`Clue::information`] as clues are loaded
+        pub fn with_text_validation(mut self, validation: TextValidation) -> Self {
+            self.text_validation = Some(validation);
+            self
+        }
+    }
+
+    impl Default for CluesAssetPlugin {
+        fn default() -> Self {
+            Self {
+                extensions: AssetExtensions::new("clues.yml"),
+                text_validation: None,
+                id_strategy: IdStrategy::default(),
+            }
+        }
+    }
+
+    impl Plugin for CluesAssetPlugin {
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_asset::<Clues>()
+                .add_asset_loader(CluesAssetLoader {
+                    extensions: self.extensions.get(),
+                    text_validation: self.text_validation,
+                    id_strategy: self.id_strategy,
+                })
+                .register_type::<Clue>()
+                .register_type::<ClueId>()
+                .register_type::<LocalizedText>()
+                .register_type::<PersonId>()
+                .add_event::<DumpIndices>()
+                .add_system(dump_indices);
+        }
+    }
+
+    struct CluesAssetLoader {
+        extensions: Vec<&'static str>,
+        text_validation: Option<TextValidation>,
+        id_strategy: IdStrategy,
+    }
     impl AssetLoader for CluesAssetLoader {
         fn load<'a>(
             &'a self,
@@ -162,7 +2394,15 @@ mod assets {
             load_context: &'a mut bevy::asset::LoadContext,
         ) -> bevy::utils::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
             Box::pin(async move {
-                let clues_file: CluesFile = serde_yaml::from_slice(bytes)?;
+                let mut clues_file: CluesFile = yaml::from_slice(bytes)?;
+                clues_file.assign_ids(self.id_strategy);
+                if let Some(validation) = &self.text_validation {
+                    for clue in &clues_file.clues {
+                        for text in clue.information.iter() {
+                            validation.check("clue", &clue.id.0, text);
+                        }
+                    }
+                }
                 let clues: Clues = clues_file.into();
                 load_context.set_default_asset(LoadedAsset::new(clues));
                 Ok(())
@@ -170,7 +2410,1219 @@ mod assets {
         }
 
         fn extensions(&self) -> &[&str] {
-            &[".clues.yml"]
+            &self.extensions
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clues(yaml: &str) -> Clues {
+        serde_yaml::from_str::<CluesFile>(yaml).unwrap().into()
+    }
+
+    fn ids<'a>(clues: impl IntoIterator<Item = &'a Clue>) -> Vec<&'a str> {
+        let mut ids: Vec<_> = clues.into_iter().map(|c| c.id.0.as_str()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn unreachable_lists_only_floating_clues() {
+        let clues = clues(
+            "clues:
+- {id: floating, information: x, persons: [], locations: []}
+- {id: knife, information: x, persons: [bob], locations: []}
+- {id: cup, information: x, persons: [], locations: [hall]}
+",
+        );
+        assert_eq!(ids(clues.unreachable()), ["floating"]);
+    }
+
+    fn person(id: &str) -> PersonId {
+        PersonId(id.to_string())
+    }
+
+    fn location(id: &str) -> LocationId {
+        serde_yaml::from_str(id).unwrap()
+    }
+
+    #[test]
+    fn person_and_location_yields_shared_clue_once() {
+        let clues = clues(
+            "clues:
+- {id: knife, information: x, persons: [bob, bob], locations: [kitchen, kitchen]}
+- {id: cup, information: x, persons: [bob], locations: [hall]}
+- {id: coat, information: x, persons: [alice], locations: [kitchen]}
+",
+        );
+        assert_eq!(
+            ids(clues.get_by_person_and_location(&person("bob"), &location("kitchen"))),
+            ["knife"]
+        );
+        assert_eq!(ids(clues.get_by_person(&person("bob"))), ["cup", "knife"]);
+    }
+
+    #[test]
+    fn person_locations_yield_every_clue_there_once() {
+        let clues = clues(
+            "clues:
+- {id: knife, information: x, persons: [bob], locations: [kitchen]}
+- {id: cup, information: x, persons: [bob], locations: [hall]}
+- {id: coat, information: x, persons: [alice], locations: [kitchen, hall]}
+- {id: boat, information: x, persons: [alice], locations: [docks]}
+",
+        );
+        assert_eq!(
+            ids(clues.get_for_person_locations(&person("bob"))),
+            ["coat", "cup", "knife"]
+        );
+    }
+
+    #[test]
+    fn changed_since_lists_only_the_modified_clue() {
+        let mut clues = clues("clues:\n- {id: a, information: x, persons: [], locations: []}\n- {id: b, information: x, persons: [], locations: []}\n");
+        let seen = clues.revision();
+        assert_eq!(clues.changed_since(seen).count(), 0);
+
+        assert!(clues.set_known(&ClueId("a".to_string()), true));
+        assert_eq!(ids(clues.changed_since(seen)), ["a"]);
+    }
+
+    #[test]
+    fn person_clues_are_grouped_by_location() {
+        let clues = clues(
+            "clues:
+- {id: knife, information: x, persons: [bob], locations: [kitchen]}
+- {id: cup, information: x, persons: [bob], locations: [hall, kitchen]}
+- {id: coat, information: x, persons: [alice], locations: [porch]}
+",
+        );
+        let grouped = clues.person_clues_by_location(&person("bob"));
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(
+            ids(grouped[&location("kitchen")].iter().copied()),
+            ["cup", "knife"]
+        );
+        assert_eq!(ids(grouped[&location("hall")].iter().copied()), ["cup"]);
+    }
+
+    #[test]
+    fn discovery_source_is_kept_with_the_reveal() {
+        let mut clues =
+            clues("clues:\n- {id: knife, information: x, persons: [bob], locations: [kitchen]}\n");
+        let knife = ClueId("knife".to_string());
+        let source = DiscoverySource {
+            person: Some(person("bob")),
+            location: Some(location("kitchen")),
+            time: Some(12),
+        };
+        assert_eq!(clues.discovery_source(&knife), None);
+        assert!(clues.reveal_with_source(&knife, source.clone()));
+        assert!(clues.get(&knife).unwrap().known);
+        assert_eq!(clues.discovery_source(&knife), Some(&source));
+        assert!(!clues.reveal_with_source(&ClueId("cup".to_string()), source));
+    }
+
+    #[test]
+    fn clues_can_be_looked_up_by_str() {
+        let clues = clues("clues:\n- {id: knife, information: x, persons: [], locations: []}\n");
+        assert_eq!(
+            clues.get_str("knife").unwrap().id,
+            ClueId("knife".to_string())
+        );
+        assert!(clues.get_str("cup").is_none());
+        let locations: HashMap<LocationId, ()> = [(location("hall"), ())].into_iter().collect();
+        assert!(locations.contains_key("hall"));
+    }
+
+    /// A directory under the system's temporary directory, empty and
+    /// unique to the test
+    fn temp_dir(test: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("revealer-{}-{}", std::process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn clue_files_in_a_directory_are_merged() {
+        let dir = temp_dir("clue_files_in_a_directory_are_merged");
+        fs::write(
+            dir.join("1.clues.yml"),
+            "clues:\n- {id: knife, information: x, persons: [bob], locations: []}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("2.clues.yml"),
+            "clues:\n- {id: cup, information: x, persons: [bob], locations: []}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "not clues").unwrap();
+        let clues = load_clues_dir(&dir).unwrap();
+        assert_eq!(ids(clues.get_by_person(&person("bob"))), ["cup", "knife"]);
+
+        fs::write(
+            dir.join("3.clues.yml"),
+            "clues:\n- {id: knife, information: again, persons: [], locations: []}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_clues_dir(&dir),
+            Err(ClueError::DuplicateId(id)) if id == ClueId("knife".to_string())
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn anchored_persons_resolve_for_both_clues() {
+        let clues = clues(
+            "clues:
+  - id: stolen_ledger
+    persons: &smugglers [captain_rhys, old_mag]
+    locations: [docks]
+    information: The harbour ledger is missing.
+  - id: late_shipment
+    persons: *smugglers
+    locations: [warehouse]
+    information: A shipment arrived after curfew.
+",
+        );
+        let ledger = clues.get_str("stolen_ledger").unwrap();
+        let shipment = clues.get_str("late_shipment").unwrap();
+        assert_eq!(ledger.persons, [person("captain_rhys"), person("old_mag")]);
+        assert_eq!(ledger.persons, shipment.persons);
+        assert_eq!(
+            ids(clues.get_by_person(&person("old_mag"))),
+            ["late_shipment", "stolen_ledger"]
+        );
+    }
+
+    #[test]
+    fn top_orders_by_importance_then_id() {
+        let clues = clues(
+            "clues:
+- {id: minor, information: x, persons: [], locations: [], importance: 1}
+- {id: major, information: x, persons: [], locations: [], importance: 90}
+- {id: also_major, information: x, persons: [], locations: [], importance: 90}
+- {id: default, information: x, persons: [], locations: []}
+",
+        );
+        let top: Vec<_> = clues
+            .top(2, false)
+            .iter()
+            .map(|c| c.id.0.as_str())
+            .collect();
+        assert_eq!(top, ["also_major", "major"]);
+    }
+
+    #[test]
+    fn red_herrings_can_be_left_out_of_the_frontier() {
+        let clues = clues(
+            "clues:
+- {id: start, information: x, persons: [], locations: []}
+- {id: lead, information: x, persons: [], locations: [], requires: [start]}
+- {id: lie, information: x, persons: [], locations: [], requires: [start], red_herring: true}
+- {id: later, information: x, persons: [], locations: [], requires: [lead]}
+",
+        );
+        let mut known = CluesComponent::default();
+        known.insert(ClueId("start".to_string()));
+        assert_eq!(ids(clues.frontier(&known, false)), ["lead", "lie"]);
+        assert_eq!(ids(clues.frontier(&known, true)), ["lead"]);
+        assert_eq!(clues.top(4, true).len(), 3);
+    }
+
+    #[test]
+    fn only_fully_dangling_clues_are_pruned() {
+        let mut clues = clues(
+            "clues:
+- {id: dangling, information: x, persons: [ghost], locations: [nowhere]}
+- {id: partly, information: x, persons: [bob, ghost], locations: [nowhere]}
+- {id: floating, information: x, persons: [], locations: []}
+",
+        );
+        let (locations, _) = crate::locations::load_files(&[]);
+        let (persons, _) = crate::persons::load_files(&[b"persons:
+- {id: bob, name: Bob, info: null}
+"
+        .to_vec()]);
+        let pruned = clues.prune_dangling(&locations, &persons);
+        assert_eq!(pruned, [ClueId("dangling".to_string())]);
+        assert!(clues.get_str("dangling").is_none());
+        assert!(clues.get_str("partly").is_some());
+        assert!(clues.get_str("floating").is_some());
+        assert_eq!(clues.get_by_location(&location("nowhere")).count(), 1);
+    }
+
+    const TOWN: &str = "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town], info: null}
+- {id: cellar, name: Cellar, parent_locations: [inn], info: null}
+- {id: well, name: Well, parent_locations: [town], info: null}
+";
+
+    fn town() -> Locations {
+        Locations::from_reader(TOWN.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn clues_above_a_location_show_up_for_it() {
+        let clues = clues(
+            "clues:
+- {id: rumour, information: x, persons: [], locations: [town]}
+- {id: stain, information: x, persons: [], locations: [cellar]}
+- {id: bucket, information: x, persons: [], locations: [well]}
+- {id: mug, information: x, persons: [], locations: [inn, town]}
+",
+        );
+        assert_eq!(
+            ids(clues.get_at_or_above(&town(), &location("cellar"))),
+            ["mug", "rumour", "stain"]
+        );
+    }
+
+    #[test]
+    fn page_splits_results_by_id() {
+        let yaml: String = (0..10)
+            .map(|i| {
+                format!(
+                    "- {{id: c{}, information: x, persons: [bob], locations: []}}\n",
+                    i
+                )
+            })
+            .collect();
+        let clues = clues(&format!("clues:\n{}", yaml));
+        let pages: Vec<_> = (0..4)
+            .map(|page| ids(clues.page(ClueQuery::ByPerson(person("bob")), page * 3, 3)))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                vec!["c0", "c1", "c2"],
+                vec!["c3", "c4", "c5"],
+                vec!["c6", "c7", "c8"],
+                vec!["c9"]
+            ]
+        );
+        assert!(clues.page(ClueQuery::All, 10, 3).is_empty());
+    }
+
+    #[test]
+    fn filter_builder_ands_conditions() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [bob], locations: [], tags: [murder], known: true}
+- {id: b, information: x, persons: [bob], locations: [], tags: [murder]}
+- {id: c, information: x, persons: [bob], locations: [], known: true}
+- {id: d, information: x, persons: [ann], locations: [], tags: [murder], known: true}
+",
+        );
+        let filter = ClueQuery::builder()
+            .person(person("bob"))
+            .tag("murder")
+            .known(true);
+        assert_eq!(ids(filter.execute(&clues)), ["a"]);
+        assert_eq!(ids(clues.page(ClueQuery::Matching(filter), 0, 10)), ["a"]);
+    }
+
+    #[test]
+    fn related_within_stops_after_max_hops() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [], locations: [], related_clues: [b]}
+- {id: b, information: x, persons: [], locations: [], related_clues: [c, a]}
+- {id: c, information: x, persons: [], locations: []}
+",
+        );
+        let a = ClueId("a".to_string());
+        assert_eq!(clues.related_within(&a, 1), [ClueId("b".to_string())]);
+        assert_eq!(
+            clues.related_within(&a, 2),
+            [ClueId("b".to_string()), ClueId("c".to_string())]
+        );
+        assert!(clues.related_within(&a, 0).is_empty());
+    }
+
+    #[test]
+    fn markdown_has_a_section_per_known_clue() {
+        let clues = clues(
+            "clues:
+- {id: knife, information: Bloody knife, persons: [bob], locations: [kitchen], known: true}
+- {id: secret, information: Hidden passage, persons: [], locations: [cellar]}
+",
+        );
+        let markdown = clues.to_markdown(true);
+        assert_eq!(
+            markdown,
+            "## knife\n\nBloody knife\n\n### Persons\n\n- bob\n\n### Locations\n\n- kitchen\n\n"
+        );
+        assert!(clues
+            .to_markdown(false)
+            .contains("## secret\n\nHidden passage\n"));
+    }
+
+    #[test]
+    fn markdown_falls_back_to_the_first_language() {
+        let clues = clues(
+            "clues:
+- id: letter
+  information:
+    fr: Une lettre
+    de: Ein Brief
+  persons: []
+  locations: []
+",
+        );
+        assert_eq!(clues.to_markdown(false), "## letter\n\nEin Brief\n\n");
+    }
+
+    #[test]
+    fn information_can_be_plain_or_localized() {
+        let clues = clues(
+            "clues:
+- {id: plain, information: The door was forced., persons: [], locations: []}
+- id: localized
+  information:
+    en: The door was forced.
+    fr: La porte a été forcée.
+  persons: []
+  locations: []
+",
+        );
+        let plain = clues.get_str("plain").unwrap();
+        assert_eq!(plain.information_in("en"), Some("The door was forced."));
+        assert_eq!(plain.information_in("fr"), Some("The door was forced."));
+        let localized = clues.get_str("localized").unwrap();
+        assert_eq!(
+            localized.information_in("fr"),
+            Some("La porte a été forcée.")
+        );
+        assert_eq!(localized.information_in("de"), Some("The door was forced."));
+    }
+
+    #[test]
+    fn dump_indices_lists_every_index_sorted() {
+        let clues = clues("clues:\n- {id: b, information: x, persons: [bob], locations: [inn], tags: [t]}\n- {id: a, information: x, persons: [bob], locations: []}\n");
+        let dump = clues.dump_indices();
+        assert!(dump.starts_with("by_person:\n"));
+        assert!(dump.contains("  inn: b\n"));
+        assert!(dump.contains("by_tag:\n  t: b\n"));
+        let bob = dump.lines().find(|l| l.starts_with("  bob: ")).unwrap();
+        assert!(bob == "  bob: a, b" || bob == "  bob: b, a");
+    }
+
+    #[test]
+    fn clues_are_only_active_in_their_window() {
+        let clues = clues(
+            "clues:
+- {id: window, information: x, persons: [], locations: [], valid_from: 10, valid_until: 20}
+- {id: always, information: x, persons: [], locations: []}
+",
+        );
+        let window = clues.get_str("window").unwrap();
+        assert!(window.is_active_at(10) && window.is_active_at(20));
+        assert!(!window.is_active_at(9), "not valid yet");
+        assert!(!window.is_active_at(21), "expired");
+        assert_eq!(ids(clues.active_at(15)), ["always", "window"]);
+        assert_eq!(ids(clues.active_at(30)), ["always"]);
+    }
+
+    #[test]
+    fn busiest_location_breaks_ties_by_id() {
+        let winner = clues(
+            "clues:
+- {id: a, information: x, persons: [], locations: [hall, kitchen]}
+- {id: b, information: x, persons: [], locations: [kitchen]}
+",
+        );
+        assert_eq!(winner.busiest_location(), Some((&location("kitchen"), 2)));
+        let tie = clues("clues:\n- {id: a, information: x, persons: [], locations: [kitchen]}\n- {id: b, information: x, persons: [], locations: [hall]}\n");
+        assert_eq!(tie.busiest_location(), Some((&location("hall"), 1)));
+        assert_eq!(Clues::new().busiest_location(), None);
+    }
+
+    #[test]
+    fn plugin_registers_reflected_types() {
+        use bevy::{
+            asset::AssetPlugin,
+            prelude::{App, MinimalPlugins},
+            reflect::{Struct, TypeRegistryArc},
+        };
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_plugin(CluesAssetPlugin::default());
+        let registry = app.world.resource::<TypeRegistryArc>().read();
+        assert!(registry.get(std::any::TypeId::of::<Clue>()).is_some());
+        assert!(registry.get(std::any::TypeId::of::<ClueId>()).is_some());
+        assert!(registry.get(std::any::TypeId::of::<PersonId>()).is_some());
+        assert!(registry
+            .get(std::any::TypeId::of::<LocalizedText>())
+            .is_some());
+
+        // The clue text is visible through reflection
+        let clue: Clue = serde_yaml::from_str("[knife, A bloody knife, [], []]").unwrap();
+        let information = clue
+            .field("information")
+            .and_then(|f| f.downcast_ref::<LocalizedText>())
+            .unwrap();
+        assert_eq!(information.get(DEFAULT_LANGUAGE), Some("A bloody knife"));
+    }
+
+    // The default strict id pattern doesn't allow uppercase ids
+    #[cfg(not(feature = "strict-ids"))]
+    #[test]
+    fn ids_differing_only_by_case_conflict() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [Bob], locations: [Docks]}
+- {id: b, information: x, persons: [bob], locations: [docks, hall]}
+",
+        );
+        assert_eq!(
+            clues.casing_conflicts(),
+            [(location("Docks"), location("docks"))]
+        );
+        assert_eq!(
+            clues.person_casing_conflicts(),
+            [(person("Bob"), person("bob"))]
+        );
+    }
+
+    #[test]
+    fn dossier_has_the_person_clues_and_locations() {
+        let clues = clues(
+            "clues:
+- {id: knife, information: Bloody knife, persons: [bob, alice], locations: [inn]}
+- {id: coat, information: x, persons: [alice], locations: [well]}
+",
+        );
+        let dossier = clues.person_dossier(&person("bob"), &town());
+        assert_eq!(
+            serde_yaml::to_string(&dossier).unwrap(),
+            "person: bob
+clues:
+- id: knife
+  information: Bloody knife
+  known: false
+locations:
+- id: inn
+  name: Inn
+associates:
+- alice
+"
+        );
+    }
+
+    fn known(ids: &[&str]) -> CluesComponent {
+        let mut known = CluesComponent::default();
+        for id in ids {
+            known.insert(ClueId(id.to_string()));
         }
+        known
+    }
+
+    #[test]
+    fn only_known_contradictions_are_active() {
+        let clues = clues(
+            "clues:
+- {id: alibi, information: x, persons: [], locations: [], contradicts: [witness, bloodstain]}
+- {id: witness, information: x, persons: [], locations: []}
+- {id: bloodstain, information: x, persons: [], locations: []}
+",
+        );
+        let id = |id: &str| ClueId(id.to_string());
+        assert_eq!(
+            clues.active_contradictions(&known(&["alibi", "witness"])),
+            [(id("alibi"), id("witness"))]
+        );
+        assert!(clues
+            .active_contradictions(&known(&["witness", "bloodstain"]))
+            .is_empty());
+    }
+
+    #[test]
+    fn known_snapshot_restores_the_flags() {
+        let mut clues = clues("clues:\n- {id: a, information: x, persons: [], locations: []}\n- {id: b, information: x, persons: [], locations: [], known: true}\n");
+        let snapshot = clues.known_snapshot();
+        clues.set_known(&ClueId("a".to_string()), true);
+        clues.set_known(&ClueId("b".to_string()), false);
+        clues.apply_known_snapshot(&snapshot);
+        assert!(!clues.get_str("a").unwrap().known);
+        assert!(clues.get_str("b").unwrap().known);
+    }
+
+    #[test]
+    fn known_clues_missing_prerequisites_are_inconsistent() {
+        let clues = clues(
+            "clues:
+- {id: start, information: x, persons: [], locations: []}
+- {id: fine, information: x, persons: [], locations: [], requires: [start]}
+- {id: skipped, information: x, persons: [], locations: [], requires: [fine]}
+",
+        );
+        let known = known(&["start", "skipped"]);
+        assert_eq!(ids(clues.inconsistent_known(&known)), ["skipped"]);
+    }
+
+    #[test]
+    fn known_persons_come_from_known_clues() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [bob, alice], locations: []}
+- {id: b, information: x, persons: [alice], locations: []}
+- {id: c, information: x, persons: [eve], locations: []}
+",
+        );
+        let persons: Vec<_> = clues.known_persons(&known(&["a", "b"])).cloned().collect();
+        assert_eq!(persons, [person("alice"), person("bob")]);
+    }
+
+    #[test]
+    fn known_locations_come_from_known_clues() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [], locations: [kitchen, hall]}
+- {id: b, information: x, persons: [], locations: [hall]}
+- {id: c, information: x, persons: [], locations: [cellar]}
+",
+        );
+        let locations: Vec<_> = clues
+            .known_locations(&known(&["a", "b"]))
+            .cloned()
+            .collect();
+        assert_eq!(locations, [location("hall"), location("kitchen")]);
+    }
+
+    #[test]
+    fn csv_columns_can_be_in_any_order() {
+        let clues = load_clues_csv(
+            b"locations,id,persons,information,notes
+kitchen;hall,knife,bob, Bloody knife,ignored
+,cup,,Empty cup,
+",
+        )
+        .unwrap();
+        let knife = clues.get_str("knife").unwrap();
+        assert_eq!(knife.locations, [location("kitchen"), location("hall")]);
+        assert_eq!(knife.persons, [person("bob")]);
+        assert_eq!(knife.information_in("en"), Some(" Bloody knife"));
+        assert!(clues.get_str("cup").unwrap().persons.is_empty());
+        assert!(matches!(
+            load_clues_csv(b"id,information,persons,locations\nknife,a,,\nknife,b,,\n"),
+            Err(ClueError::DuplicateId(_))
+        ));
+        assert!(matches!(
+            load_clues_csv(b"id,information,persons\n"),
+            Err(ClueError::Csv(_))
+        ));
+    }
+
+    #[test]
+    fn broken_campaign_reports_every_error() {
+        let dir = temp_dir("broken_campaign_reports_every_error");
+        fs::write(
+            dir.join("a.clues.yml"),
+            "clues:
+- {id: knife, information: x, persons: [ghost], locations: [nowhere]}
+- {id: loop, information: x, persons: [], locations: [], requires: [loop, missing]}
+",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("a.locations.yml"),
+            "locations:\n- {id: inn, name: Inn, parent_locations: [town], info: null}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("a.persons.yml"), "persons: [").unwrap();
+        let errors = validate_campaign_dir(&dir).unwrap_err();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, ClueError::UnknownPerson { .. })));
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, ClueError::UnknownLocation { .. })));
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, ClueError::UnknownClue { .. })));
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, ClueError::RequiresCycle(_))));
+        assert!(errors.iter().any(|e| matches!(e, ClueError::Location(_))));
+        assert!(errors.iter().any(|e| matches!(e, ClueError::Yaml(_))));
+    }
+
+    #[test]
+    fn metadata_is_read_from_meta() {
+        let clues = clues(
+            "clues:
+- {id: lock, information: x, persons: [], locations: [], meta: {dc: '15', skill: thievery}}
+",
+        );
+        let lock = clues.get_str("lock").unwrap();
+        assert_eq!(lock.metadata_get("dc"), Some("15"));
+        assert_eq!(lock.metadata_get("skill"), Some("thievery"));
+        assert_eq!(lock.metadata_get("reward"), None);
+    }
+
+    #[test]
+    fn relation_path_finds_the_shortest_chain() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [], locations: [], related_clues: [b, d]}
+- {id: b, information: x, persons: [], locations: [], related_clues: [c]}
+- {id: d, information: x, persons: [], locations: [], related_clues: [c]}
+- {id: c, information: x, persons: [], locations: []}
+- {id: island, information: x, persons: [], locations: []}
+",
+        );
+        let id = |id: &str| ClueId(id.to_string());
+        assert_eq!(
+            clues.relation_path(&id("a"), &id("c"), true).unwrap().len(),
+            3
+        );
+        assert_eq!(clues.relation_path(&id("c"), &id("a"), true), None);
+        assert_eq!(
+            clues
+                .relation_path(&id("c"), &id("a"), false)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(clues.relation_path(&id("a"), &id("island"), false), None);
+    }
+
+    #[test]
+    fn clues_spanning_maps_are_flagged() {
+        let locations = Locations::from_reader(
+            "locations:
+- {id: town, name: Town, parent_locations: [], map: overworld, info: null}
+- {id: inn, name: Inn, parent_locations: [], map: town_map, info: null}
+- {id: bar, name: Bar, parent_locations: [], map: town_map, info: null}
+"
+            .as_bytes(),
+        )
+        .unwrap();
+        let clues = clues(
+            "clues:
+- {id: single, information: x, persons: [], locations: [inn, bar]}
+- {id: cross, information: x, persons: [], locations: [inn, town]}
+",
+        );
+        assert_eq!(ids(clues.cross_map_clues(&locations)), ["cross"]);
+    }
+
+    #[test]
+    fn clues_sort_by_person_count_then_id() {
+        let clues = clues(
+            "clues:
+- {id: one, information: x, persons: [bob], locations: []}
+- {id: three, information: x, persons: [bob, alice, eve], locations: []}
+- {id: also_one, information: x, persons: [eve], locations: []}
+- {id: none, information: x, persons: [], locations: []}
+",
+        );
+        let sorted: Vec<_> = clues
+            .by_person_count_desc()
+            .iter()
+            .map(|c| c.id.0.as_str())
+            .collect();
+        assert_eq!(sorted, ["three", "also_one", "one", "none"]);
+    }
+
+    #[test]
+    fn between_is_inclusive_and_skips_timeless_clues() {
+        let clues = clues(
+            "clues:
+- {id: early, information: x, persons: [], locations: [], time: 1}
+- {id: start, information: x, persons: [], locations: [], time: 5}
+- {id: end, information: x, persons: [], locations: [], time: 10}
+- {id: late, information: x, persons: [], locations: [], time: 11}
+- {id: timeless, information: x, persons: [], locations: []}
+",
+        );
+        assert_eq!(ids(clues.between(5, 10)), ["end", "start"]);
+    }
+
+    #[test]
+    fn retain_keeps_the_indices_in_step() {
+        let mut clues = clues(
+            "clues:
+- {id: seen, information: x, persons: [bob], locations: [hall], tags: [murder], known: true}
+- {id: unseen, information: x, persons: [bob], locations: [hall], tags: [theft]}
+",
+        );
+        clues.retain(|c| c.known);
+        assert_eq!(ids(clues.iter()), ["seen"]);
+        assert_eq!(ids(clues.get_by_person(&person("bob"))), ["seen"]);
+        assert_eq!(ids(clues.get_by_location(&location("hall"))), ["seen"]);
+        assert_eq!(clues.tags().collect::<Vec<_>>(), ["murder"]);
+    }
+
+    #[test]
+    fn reach_counts_a_location_and_its_descendants() {
+        let clues = clues(
+            "clues:
+- {id: decree, information: x, persons: [], locations: [town]}
+- {id: rumour, information: x, persons: [], locations: [inn, cellar, well]}
+",
+        );
+        let reach = |id: &str| clues.clue_reach(&town(), &ClueId(id.to_string()));
+        assert_eq!(reach("decree"), 4);
+        assert_eq!(reach("rumour"), 3);
+        assert_eq!(reach("missing"), 0);
+    }
+
+    #[test]
+    fn overlapping_matches_are_paired_with_each_person() {
+        let clues = clues(
+            "clues:
+- {id: both, information: x, persons: [bob, alice], locations: []}
+- {id: bobs, information: x, persons: [bob], locations: []}
+",
+        );
+        let persons = [person("bob"), person("alice")];
+        let mut matches: Vec<_> = clues
+            .get_by_persons_tagged(&persons)
+            .map(|(c, p)| (c.id.0.as_str(), p.0.as_str()))
+            .collect();
+        matches.sort();
+        assert_eq!(
+            matches,
+            [("bobs", "bob"), ("both", "alice"), ("both", "bob")]
+        );
+    }
+
+    #[test]
+    fn bulk_reveal_skips_invalid_and_known_ids() {
+        let mut clues = clues(
+            "clues:
+- {id: a, information: x, persons: [], locations: []}
+- {id: b, information: x, persons: [], locations: [], known: true}
+- {id: c, information: x, persons: [], locations: []}
+",
+        );
+        let id = |id: &str| ClueId(id.to_string());
+        assert_eq!(clues.set_known_bulk(&[id("a"), id("b"), id("missing")]), 1);
+        assert!(clues.get_str("a").unwrap().known);
+        assert!(!clues.get_str("c").unwrap().known);
+    }
+
+    #[test]
+    fn clues_can_be_found_by_their_information() {
+        let clues = clues(
+            "clues:
+- {id: knife, information: Bloody knife, persons: [], locations: []}
+- {id: copy_a, information: Footprints, persons: [], locations: []}
+- {id: copy_b, information: Footprints, persons: [], locations: []}
+",
+        );
+        assert_eq!(
+            clues.find_by_information("Bloody knife"),
+            Some(&ClueId("knife".to_string()))
+        );
+        assert_eq!(
+            clues.find_all_by_information("Footprints"),
+            [&ClueId("copy_a".to_string()), &ClueId("copy_b".to_string())]
+        );
+        assert_eq!(clues.find_by_information("Nothing"), None);
+    }
+
+    #[test]
+    fn reveal_distance_counts_prerequisite_layers() {
+        let clues = clues(
+            "clues:
+- {id: start, information: x, persons: [], locations: []}
+- {id: middle, information: x, persons: [], locations: [], requires: [start]}
+- {id: end, information: x, persons: [], locations: [], requires: [middle, start]}
+- {id: stuck, information: x, persons: [], locations: [], requires: [missing]}
+",
+        );
+        let id = |id: &str| ClueId(id.to_string());
+        let none = CluesComponent::default();
+        assert_eq!(clues.reveal_distance(&id("start"), &none), Some(0));
+        assert_eq!(clues.reveal_distance(&id("end"), &none), Some(2));
+        assert_eq!(
+            clues.reveal_distance(&id("end"), &known(&["start"])),
+            Some(1)
+        );
+        assert_eq!(clues.reveal_distance(&id("stuck"), &none), None);
+    }
+
+    #[test]
+    fn filter_expressions_combine() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [bob], locations: [hall], known: true}
+- {id: b, information: x, persons: [bob], locations: [kitchen]}
+- {id: c, information: x, persons: [alice], locations: [hall], tags: [theft]}
+",
+        );
+        let filter = ClueFilter::And(vec![
+            ClueFilter::Or(vec![
+                ClueFilter::HasPerson(person("bob")),
+                ClueFilter::HasTag("theft".to_string()),
+            ]),
+            ClueFilter::Not(Box::new(ClueFilter::Known(true))),
+        ]);
+        assert_eq!(ids(clues.query_expr(&filter)), ["b", "c"]);
+        let hall = ClueFilter::And(vec![filter, ClueFilter::HasLocation(location("hall"))]);
+        assert_eq!(ids(clues.query_expr(&hall)), ["c"]);
+        assert_eq!(clues.query_expr(&ClueFilter::And(vec![])).count(), 3);
+        assert_eq!(clues.query_expr(&ClueFilter::Or(vec![])).count(), 0);
+    }
+
+    #[test]
+    fn compact_and_full_forms_are_equal() {
+        let compact: Clue =
+            serde_yaml::from_str("[knife, Bloody knife, [bob], [kitchen]]").unwrap();
+        let full: Clue = serde_yaml::from_str(
+            "id: knife
+information: Bloody knife
+persons: [bob]
+locations: [kitchen]
+",
+        )
+        .unwrap();
+        assert_eq!(compact.id, full.id);
+        assert!(compact.changed_fields(&full).is_empty());
+        assert!(serde_yaml::from_str::<Clue>("[knife, x, [], [], extra]").is_err());
+        assert!(serde_yaml::from_str::<Clue>("[knife, x, []]").is_err());
+    }
+
+    #[test]
+    fn clue_maps_lists_each_map_once() {
+        let locations = Locations::from_reader(
+            "locations:
+- {id: town, name: Town, parent_locations: [], map: overworld, info: null}
+- {id: inn, name: Inn, parent_locations: [], map: town_map, info: null}
+- {id: bar, name: Bar, parent_locations: [], map: town_map, info: null}
+"
+            .as_bytes(),
+        )
+        .unwrap();
+        let clues = clues("clues:\n- [letter, x, [], [town, inn, bar, nowhere]]\n");
+        let maps = clues.clue_maps(&locations, &ClueId("letter".to_string()));
+        let expected: HashSet<MapId> = serde_yaml::from_str("[overworld, town_map]").unwrap();
+        assert_eq!(maps, expected);
+    }
+
+    #[test]
+    fn most_co_occurring_person_is_suggested_first() {
+        let clues = clues(
+            "clues:
+- [a, x, [bob, alice], []]
+- [b, x, [bob, alice, eve], []]
+- [c, x, [bob, mallory], []]
+- [d, x, [trent], []]
+",
+        );
+        assert_eq!(
+            clues.suggest_persons(&[person("bob")], 2),
+            [person("alice"), person("eve")]
+        );
+        assert_eq!(clues.suggest_persons(&[person("bob")], 10).len(), 3);
+    }
+
+    #[test]
+    fn cloning_frozen_clues_shares_storage() {
+        let frozen = clues("clues:\n- [a, x, [], []]\n").freeze();
+        let copy = frozen.clone();
+        assert!(frozen.ptr_eq(&copy));
+        assert!(!frozen.ptr_eq(&Clues::new().freeze()));
+        assert!(copy.get_str("a").is_some());
+    }
+
+    #[test]
+    fn partitions_by_known_are_disjoint() {
+        let clues = clues("clues:\n- [a, x, [], []]\n- [b, x, [], []]\n- [c, x, [], []]\n");
+        let component = known(&["b", "missing"]);
+        let (known, unknown) = clues.partition_known(&component);
+        assert_eq!(ids(known), ["b"]);
+        assert_eq!(ids(unknown), ["a", "c"]);
+    }
+
+    #[test]
+    fn updating_persons_moves_the_clue_between_queries() {
+        let mut clues = clues("clues:\n- [knife, x, [bob], []]\n");
+        let knife = ClueId("knife".to_string());
+        assert!(clues.update_clue(&knife, |c| c.persons = vec![person("alice")]));
+        assert_eq!(clues.get_by_person(&person("bob")).count(), 0);
+        assert_eq!(ids(clues.get_by_person(&person("alice"))), ["knife"]);
+        assert!(!clues.update_clue(&ClueId("missing".to_string()), |_| {}));
+    }
+
+    #[test]
+    fn tags_are_listed_once_and_counted() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [], locations: [], tags: [murder, night]}
+- {id: b, information: x, persons: [], locations: [], tags: [night]}
+",
+        );
+        assert_eq!(clues.tags().collect::<Vec<_>>(), ["murder", "night"]);
+        let counts = clues.tag_counts();
+        assert_eq!((counts["murder"], counts["night"]), (1, 2));
+    }
+
+    #[test]
+    fn clues_and_locations_load_from_a_cursor() {
+        let clues =
+            Clues::from_reader(std::io::Cursor::new("clues:\n- [a, x, [], [inn]]\n")).unwrap();
+        assert_eq!(ids(clues.iter()), ["a"]);
+        let locations = Locations::from_reader(std::io::Cursor::new(TOWN)).unwrap();
+        assert_eq!(locations.iter().count(), 4);
+        assert!(Clues::from_reader(std::io::Cursor::new("clues: {")).is_err());
+    }
+
+    #[test]
+    fn investigation_component_leaves_out_unrelated_clues() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [bob], locations: [], related_clues: [b]}
+- {id: c, information: x, persons: [], locations: [], related_clues: [b]}
+- [b, x, [], []]
+- [unrelated, x, [bob], []]
+",
+        );
+        let component = clues.investigation_component(&ClueId("c".to_string()));
+        assert_eq!(ids(component.iter()), ["a", "b", "c"]);
+        assert_eq!(ids(component.get_by_person(&person("bob"))), ["a"]);
+        assert_eq!(
+            clues
+                .investigation_component(&ClueId("missing".to_string()))
+                .iter()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn ids_one_edit_apart_are_near_duplicates() {
+        let clues = clues(
+            "clues:
+- [bloody_knife, x, [], []]
+- [bloddy_knife, x, [], []]
+- [rope, x, [], []]
+",
+        );
+        let id = |id: &str| ClueId(id.to_string());
+        assert_eq!(
+            clues.near_duplicate_ids(1),
+            [(id("bloddy_knife"), id("bloody_knife"))]
+        );
+        assert!(clues.near_duplicate_ids(0).is_empty());
+    }
+
+    #[test]
+    fn generated_ids_are_stable() {
+        let yaml = "clues:
+- {information: first, persons: [], locations: []}
+- [clue_1, taken, [], []]
+- {information: second, persons: [], locations: []}
+";
+        let mut file: CluesFile = yaml::from_slice(yaml.as_bytes()).unwrap();
+        file.assign_ids(IdStrategy::Sequential);
+        let sequential: Vec<_> = file.clues.iter().map(|c| c.id.0.as_str()).collect();
+        assert_eq!(sequential, ["clue_2", "clue_1", "clue_3"]);
+
+        let hashed = |yaml: &str| {
+            let mut file: CluesFile = yaml::from_slice(yaml.as_bytes()).unwrap();
+            file.assign_ids(IdStrategy::InformationHash);
+            let mut ids: Vec<_> = file.clues.into_iter().map(|c| c.id.0).collect();
+            ids.sort();
+            ids
+        };
+        let reordered = "clues:
+- {information: second, persons: [], locations: []}
+- [clue_1, taken, [], []]
+- {information: first, persons: [], locations: []}
+";
+        assert_eq!(hashed(yaml), hashed(reordered));
+    }
+
+    #[test]
+    fn only_clues_solely_referencing_a_person_would_be_orphaned() {
+        let clues = clues(
+            "clues:
+- [diary, x, [bob, bob], []]
+- [meeting, x, [bob, alice], []]
+",
+        );
+        let orphaned: Vec<_> = clues
+            .clues_only_referencing_person(&person("bob"))
+            .iter()
+            .map(|c| c.id.0.as_str())
+            .collect();
+        assert_eq!(orphaned, ["diary"]);
+    }
+
+    #[test]
+    fn resolved_clue_has_the_names() {
+        let clues = clues("clues:\n- [knife, x, [bob, ghost], [inn, nowhere]]\n");
+        let (persons, _) = crate::persons::load_files(&[
+            b"persons:\n- {id: bob, name: Bob, info: null}\n".to_vec(),
+        ]);
+        let locations = town();
+        let resolved = resolve_clue(clues.get_str("knife").unwrap(), &locations, &persons);
+        let persons: Vec<_> = resolved.persons.iter().map(|p| p.name.as_str()).collect();
+        let locations: Vec<_> = resolved.locations.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!((persons, locations), (vec!["Bob"], vec!["Inn"]));
+    }
+
+    #[test]
+    fn clues_can_be_filtered_by_author() {
+        let clues = clues(
+            "clues:
+- {id: a, information: x, persons: [], locations: [], author: yui}
+- {id: b, information: x, persons: [], locations: [], author: sam}
+- [c, x, [], []]
+",
+        );
+        assert_eq!(clues.get_str("a").unwrap().author.as_deref(), Some("yui"));
+        assert_eq!(ids(clues.iter_by_author("yui")), ["a"]);
+        assert_eq!(clues.iter_by_author("nobody").count(), 0);
+    }
+
+    #[test]
+    fn greedy_cover_picks_the_widest_clue_first() {
+        let clues = clues(
+            "clues:
+- [a, x, [bob, alice, eve], []]
+- [b, x, [bob, alice], []]
+- [c, x, [eve, mallory], []]
+- [d, x, [mallory], []]
+- [e, x, [], [hall]]
+",
+        );
+        let cover: Vec<_> = clues
+            .min_clues_covering_persons()
+            .iter()
+            .map(|c| c.id.0.as_str())
+            .collect();
+        assert_eq!(cover, ["a", "c"]);
+    }
+
+    #[test]
+    fn person_clues_at_unknown_locations_skip_known_ones() {
+        let clues = clues(
+            "clues:
+- [seen, x, [bob], [hall]]
+- [partly_seen, x, [bob], [hall, cellar]]
+- [hidden, x, [bob], [cellar]]
+- [nowhere, x, [bob], []]
+",
+        );
+        let known_locs: HashSet<_> = [location("hall")].into_iter().collect();
+        assert_eq!(
+            ids(clues.person_clues_at_unknown_locations(&person("bob"), &known_locs)),
+            ["hidden"]
+        );
+    }
+
+    #[test]
+    fn diff_reports_adds_removes_and_edits() {
+        let old = clues(
+            "clues:\n- [kept, x, [], []]\n- [edited, Old text, [], []]\n- [gone, x, [], []]\n",
+        );
+        let new = clues(
+            "clues:\n- [kept, x, [], []]\n- [edited, New text, [], []]\n- [fresh, x, [], []]\n",
+        );
+        let diff = old.diff(&new);
+        let id = |id: &str| ClueId(id.to_string());
+        assert_eq!(diff.added, [id("fresh")]);
+        assert_eq!(diff.removed, [id("gone")]);
+        assert_eq!(
+            diff.changed,
+            [ClueChange {
+                id: id("edited"),
+                fields: vec!["information"],
+            }]
+        );
+    }
+
+    #[test]
+    fn content_hash_ignores_insertion_order() {
+        let a = clues("clues:\n- [knife, x, [bob], [hall]]\n- [cup, y, [], []]\n");
+        let b = clues("clues:\n- [cup, y, [], []]\n- [knife, x, [bob], [hall]]\n");
+        let c = clues("clues:\n- [cup, y, [], []]\n- [knife, z, [bob], [hall]]\n");
+        assert_eq!(a.content_hash(), b.content_hash());
+        assert_ne!(a.content_hash(), c.content_hash());
+    }
+
+    #[test]
+    fn hub_is_the_person_sharing_clues_with_the_most_others() {
+        let clues = clues(
+            "clues:
+- [a, x, [bob, alice], []]
+- [b, x, [bob, eve], []]
+- [c, x, [bob, mallory], []]
+- [d, x, [alice, eve], []]
+",
+        );
+        assert_eq!(clues.most_connected_person(), Some((&person("bob"), 3)));
+        assert_eq!(Clues::new().most_connected_person(), None);
+    }
+
+    #[test]
+    fn changed_since_only_lists_real_changes() {
+        let mut clues = clues("clues:\n- {id: a, information: x, persons: [], locations: []}\n- {id: b, information: x, persons: [], locations: []}\n");
+        let seen = clues.revision();
+        assert_eq!(clues.changed_since(seen).count(), 0);
+
+        // Editing without changing anything isn't a modification
+        assert!(clues.update_clue(&ClueId("a".to_string()), |_| {}));
+        assert!(!clues.set_known(&ClueId("b".to_string()), false));
+        assert_eq!(clues.revision(), seen);
+
+        clues.update_clue(&ClueId("a".to_string()), |c| c.importance = 9);
+        assert_eq!(ids(clues.changed_since(seen)), ["a"]);
+
+        // Anything borrowed mutably might have changed
+        let seen = clues.revision();
+        clues.get_mut(&ClueId("b".to_string())).unwrap();
+        assert_eq!(ids(clues.changed_since(seen)), ["b"]);
+    }
+
+    #[test]
+    fn removed_since_lists_tombstones() {
+        let mut clues = clues("clues:\n- {id: a, information: x, persons: [], locations: []}\n- {id: b, information: x, persons: [], locations: []}\n");
+        let seen = clues.revision();
+        clues.remove(&ClueId("a".to_string()));
+        assert_eq!(
+            clues.removed_since(seen).collect::<Vec<_>>(),
+            [&ClueId("a".to_string())]
+        );
+        assert_eq!(clues.removed_since(clues.revision()).count(), 0);
+
+        // Editing a clue re-inserts it, which isn't a removal
+        clues.update_clue(&ClueId("b".to_string()), |c| c.importance = 1);
+        assert_eq!(clues.removed_since(seen).count(), 1);
+        assert_eq!(ids(clues.changed_since(seen)), ["b"]);
+    }
+
+    #[test]
+    fn generated_ids_are_unique_across_files() {
+        let dir = temp_dir("generated_ids_are_unique_across_files");
+        fs::write(
+            dir.join("a.clues.yml"),
+            "clues:\n- {information: a, persons: [], locations: []}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("b.clues.yml"),
+            "clues:\n- {information: b, persons: [], locations: []}\n- [clue_2, x, [], []]\n",
+        )
+        .unwrap();
+        let clues = load_clues_dir(&dir).unwrap();
+        assert_eq!(ids(clues.iter()), ["clue_1", "clue_2", "clue_3"]);
+        assert_eq!(validate_campaign_dir(&dir).map_err(|e| e.len()), Ok(()));
+        fs::remove_dir_all(dir).unwrap();
     }
 }
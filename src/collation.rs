@@ -0,0 +1,50 @@
+//! Unicode aware ordering for sorting things by name
+use std::cmp::Ordering;
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Build a key to collate a name by.
+///
+/// The name is decomposed (NFD), any combining marks (accents, etc.) are
+/// stripped from it, and it is then lowercased. This means `"Émile"` sorts
+/// right alongside `"emile"` and before `"Fabien"`, instead of after `"Z"`
+/// as raw byte ordering would have it.
+fn collation_key(name: &str) -> String {
+    name.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Check whether two names have the same [`collation_key`], i.e. are equal
+/// once accents and case are ignored
+pub(crate) fn names_match(a: &str, b: &str) -> bool {
+    collation_key(a) == collation_key(b)
+}
+
+/// Compare two names by their [`collation_key`], falling back to the raw
+/// names so that the ordering is total.
+pub(crate) fn compare_names(a: &str, b: &str) -> Ordering {
+    collation_key(a)
+        .cmp(&collation_key(b))
+        .then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accented_names_sort_with_their_letter() {
+        let mut names = vec!["Zoé", "Fabien", "Émile", "emile"];
+        names.sort_by(|a, b| compare_names(a, b));
+        assert_eq!(names, ["emile", "Émile", "Fabien", "Zoé"]);
+    }
+
+    #[test]
+    fn names_match_ignores_accents_and_case() {
+        assert!(names_match("Émile", "emile"));
+        assert!(names_match("ZOÉ", "zoe"));
+        assert!(!names_match("Émile", "Emilie"));
+    }
+}
@@ -0,0 +1,83 @@
+//! A small CSV reader, enough to import spreadsheets exported with the
+//! usual quoting rules
+
+/// Split CSV text into records of fields. Fields can be quoted with `"`,
+/// in which case they may contain commas, newlines and doubled `""`
+/// quotes. Blank lines are skipped.
+pub(crate) fn parse(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut quote_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                if !(record.len() == 1 && record[0].is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("unterminated quoted field on line {}", quote_line));
+    }
+    record.push(field);
+    if !(record.len() == 1 && record[0].is_empty()) {
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_keep_commas_newlines_and_quotes() {
+        let records = parse("id,information\r\nknife,\"Sharp, \"\"clean\"\"\nblade\"\n\n").unwrap();
+        assert_eq!(
+            records,
+            [
+                vec!["id", "information"],
+                vec!["knife", "Sharp, \"clean\"\nblade"]
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_reports_its_line() {
+        assert_eq!(
+            parse("id\n\"knife\n").unwrap_err(),
+            "unterminated quoted field on line 2"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Identifiers that can refer to any kind of entity
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clues::{ClueId, Clues, PersonId, DEFAULT_LANGUAGE},
+    locations::{LocationId, Locations},
+    persons::Persons,
+};
+
+/// The id of a clue, location or person. When (de)serialized the kind is
+/// given by a tag, e.g. `{ kind: location, id: docks }`
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "lowercase")]
+pub enum EntityId {
+    /// The id of a clue
+    Clue(ClueId),
+    /// The id of a location
+    Location(LocationId),
+    /// The id of a person
+    Person(PersonId),
+}
+
+impl EntityId {
+    /// Get the [`ClueId`] if this refers to a clue
+    pub fn as_clue(&self) -> Option<&ClueId> {
+        match self {
+            EntityId::Clue(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Get the [`LocationId`] if this refers to a location
+    pub fn as_location(&self) -> Option<&LocationId> {
+        match self {
+            EntityId::Location(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Get the [`PersonId`] if this refers to a person
+    pub fn as_person(&self) -> Option<&PersonId> {
+        match self {
+            EntityId::Person(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Get a displayable description of whatever an [`EntityId`] refers to,
+/// the information of a clue or the name of a location or person.
+/// Returns [`None`] if it doesn't exist.
+pub fn describe(
+    id: &EntityId,
+    clues: &Clues,
+    locations: &Locations,
+    persons: &Persons,
+) -> Option<String> {
+    match id {
+        EntityId::Clue(id) => clues
+            .get(id)
+            .and_then(|c| c.information_in(DEFAULT_LANGUAGE))
+            .map(String::from),
+        EntityId::Location(id) => locations.get(id).map(|l| l.name.clone()),
+        EntityId::Person(id) => persons.get(id).map(|p| p.name.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_is_given_by_a_tag() {
+        let id: EntityId = serde_yaml::from_str("{kind: location, id: docks}").unwrap();
+        assert_eq!(
+            id.as_location(),
+            Some(&serde_yaml::from_str("docks").unwrap())
+        );
+        assert_eq!(id.as_clue(), None);
+        assert_eq!(
+            serde_yaml::to_string(&id).unwrap(),
+            "kind: location\nid: docks\n"
+        );
+    }
+
+    #[test]
+    fn describe_names_each_kind_of_entity() {
+        let clues = Clues::from_reader(
+            "clues:\n- {id: knife, information: Bloody knife, persons: [], locations: []}\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let locations = Locations::from_reader(
+            "locations:\n- {id: docks, name: The Docks, parent_locations: [], info: null}\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let (persons, _) = crate::persons::load_files(&[
+            b"persons:\n- {id: bob, name: Bob, info: null}\n".to_vec(),
+        ]);
+        let describe = |yaml: &str| {
+            describe(
+                &serde_yaml::from_str(yaml).unwrap(),
+                &clues,
+                &locations,
+                &persons,
+            )
+        };
+        assert_eq!(
+            describe("{kind: clue, id: knife}").as_deref(),
+            Some("Bloody knife")
+        );
+        assert_eq!(
+            describe("{kind: location, id: docks}").as_deref(),
+            Some("The Docks")
+        );
+        assert_eq!(describe("{kind: person, id: bob}").as_deref(), Some("Bob"));
+        assert_eq!(describe("{kind: person, id: alice}"), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! The file extensions each asset plugin loads
+
+/// The file extensions an asset loader handles, given without a leading
+/// `.`, e.g. `clues.yml`
+///
+/// [`bevy::asset::AssetLoader::extensions`] has to hand out `&str`s that
+/// live as long as the loader, so custom extensions are leaked once, when
+/// they are set on a plugin, rather than every time the plugin is built.
+#[derive(Debug, Clone)]
+pub(crate) struct AssetExtensions(Vec<&'static str>);
+
+impl AssetExtensions {
+    /// Handle just the one default extension
+    pub(crate) fn new(default: &'static str) -> Self {
+        Self(vec![default])
+    }
+
+    /// Handle `extensions` instead of the current ones. An empty list is
+    /// ignored, a loader without any extensions would never be used.
+    pub(crate) fn set(&mut self, extensions: Vec<String>) {
+        if !extensions.is_empty() {
+            self.0 = extensions
+                .into_iter()
+                .map(|ext| &*Box::leak(ext.into_boxed_str()))
+                .collect();
+        }
+    }
+
+    /// Get the extensions in the form [`bevy::asset::AssetLoader`] needs
+    pub(crate) fn get(&self) -> Vec<&'static str> {
+        self.0.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread, time::Duration};
+
+    use bevy::{
+        asset::{Asset, AssetPlugin, AssetServerSettings, LoadState},
+        prelude::{App, AssetServer, Assets, Handle, MinimalPlugins, Plugin},
+    };
+
+    use crate::{
+        clues::{Clues, CluesAssetPlugin},
+        locations::{Locations, LocationsAssetPlugin},
+        persons::{Persons, PersonsAssetPlugin},
+    };
+
+    /// Write `contents` to a file called `name` in a new asset folder and
+    /// load it with `plugin`, returning the app so the asset stays alive
+    fn load<T: Asset>(plugin: impl Plugin, name: &str, contents: &str) -> (App, Handle<T>) {
+        let folder = std::env::temp_dir().join(format!("revealer-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join(name), contents).unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(AssetServerSettings {
+            asset_folder: folder.to_string_lossy().into_owned(),
+            watch_for_changes: false,
+        })
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(plugin);
+        let handle = app.world.resource::<AssetServer>().load(name);
+        for _ in 0..500 {
+            app.update();
+            if app.world.resource::<Assets<T>>().contains(&handle) {
+                break;
+            }
+            let state = app.world.resource::<AssetServer>().get_load_state(&handle);
+            assert_ne!(state, LoadState::Failed, "{} failed to load", name);
+            thread::sleep(Duration::from_millis(10));
+        }
+        fs::remove_dir_all(folder).unwrap();
+        (app, handle)
+    }
+
+    #[test]
+    fn clues_load_under_a_custom_extension() {
+        let plugin = CluesAssetPlugin::new(vec!["clue.yaml".to_string()]);
+        let (app, handle) = load::<Clues>(
+            plugin,
+            "hall.clue.yaml",
+            "clues:\n- {id: cup, information: x, persons: [], locations: []}\n",
+        );
+        let clues = app.world.resource::<Assets<Clues>>();
+        assert_eq!(clues.get(&handle).unwrap().iter().count(), 1);
+    }
+
+    #[test]
+    fn no_extensions_keeps_the_default() {
+        let plugin = CluesAssetPlugin::new(vec![]);
+        let (app, handle) = load::<Clues>(
+            plugin,
+            "hall.clues.yml",
+            "clues:\n- {id: cup, information: x, persons: [], locations: []}\n",
+        );
+        let clues = app.world.resource::<Assets<Clues>>();
+        assert_eq!(clues.get(&handle).unwrap().iter().count(), 1);
+    }
+
+    #[test]
+    fn locations_load_under_a_custom_extension() {
+        let plugin =
+            LocationsAssetPlugin::default().with_extensions(vec!["places.yaml".to_string()]);
+        let (app, handle) = load::<Locations>(
+            plugin,
+            "town.places.yaml",
+            "locations:\n- {id: town, name: Town, parent_locations: [], info: null}\n",
+        );
+        let locations = app.world.resource::<Assets<Locations>>();
+        assert_eq!(locations.get(&handle).unwrap().iter().count(), 1);
+    }
+
+    #[test]
+    fn persons_load_under_a_custom_extension() {
+        let plugin = PersonsAssetPlugin::default().with_extensions(vec!["npcs.yaml".to_string()]);
+        let (app, handle) = load::<Persons>(
+            plugin,
+            "town.npcs.yaml",
+            "persons:\n- {id: bob, name: Bob, info: null}\n",
+        );
+        let persons = app.world.resource::<Assets<Persons>>();
+        assert_eq!(persons.get(&handle).unwrap().iter().count(), 1);
+    }
+}
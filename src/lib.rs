@@ -0,0 +1,175 @@
+//! This is a Work In Progress and represents the desired final state for the game. Most
+//! features mentioned are not actually implemented yet.
+//!
+//! Revealer is a "game" bon out of a need and desire for an easy way to manage intriuge and
+//! investigations in my D&D campaigns. It displays one of several maps at a time and
+//! allows you to mark information and certain locations and people.
+//! You can select a specific character and get a display of related locations and/or movements
+//! related to the them.
+#![deny(missing_docs)]
+
+use std::sync::OnceLock;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use clues::{Clue, ClueId, PersonId};
+use serde::{Deserialize, Serialize};
+
+pub mod clues;
+mod collation;
+mod csv;
+pub mod entity;
+mod extensions;
+pub mod loading;
+pub mod locations;
+pub mod maps;
+pub mod movements;
+pub mod persons;
+pub mod reveal;
+pub mod selection;
+pub mod snapshot;
+pub mod sync;
+#[cfg(feature = "trace")]
+mod trace;
+pub mod validation;
+mod yaml;
+
+/// One the different modes the game runs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// In server mode, there is no display, it acts only to interface with the client and server.
+    Server,
+    /// In DM mode, all [`clues_asset::Clue`]s are available and the `known` setting can be changed.
+    DM,
+    /// In player mode only the clues `known` can be seen.
+    Player,
+}
+
+/// Stores the currently known clues
+#[derive(Debug, Default, Clone, Component, Serialize, Deserialize)]
+pub struct CluesComponent {
+    clues: HashSet<ClueId>,
+}
+
+impl CluesComponent {
+    /// Check whether a clue is known
+    pub fn contains(&self, clue: &ClueId) -> bool {
+        self.clues.contains(clue)
+    }
+
+    /// Mark a clue as known, returns `true` if it wasn't already known
+    pub fn insert(&mut self, clue: ClueId) -> bool {
+        self.clues.insert(clue)
+    }
+
+    /// Get an [`Iterator`] over the known clues
+    pub fn iter(&self) -> impl Iterator<Item = &ClueId> {
+        self.clues.iter()
+    }
+}
+
+/// Stores the clues known by each player, so that different players
+/// can know different things without touching the shared [`clues::Clues`].
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PlayerKnowledge(pub HashMap<PersonId, CluesComponent>);
+
+impl PlayerKnowledge {
+    /// Reveal a clue to a single player, returns `true` if they didn't
+    /// already know it
+    pub fn reveal_to(&mut self, player: &PersonId, clue: ClueId) -> bool {
+        self.0.entry(player.clone()).or_default().insert(clue)
+    }
+
+    /// Get the clues revealed to a player, empty if they haven't had
+    /// anything revealed to them yet
+    pub fn known_by(&self, player: &PersonId) -> &CluesComponent {
+        static EMPTY: OnceLock<CluesComponent> = OnceLock::new();
+        self.0
+            .get(player)
+            .unwrap_or_else(|| EMPTY.get_or_init(CluesComponent::default))
+    }
+
+    /// Check whether a player can see a clue, either because it is known to
+    /// everyone or because it was revealed to them
+    pub fn can_see(&self, player: &PersonId, clue: &Clue) -> bool {
+        clue.known || self.known_by(player).contains(&clue.id)
+    }
+
+    /// Get who can see a clue
+    pub fn visibility(&self, clue: &Clue) -> ClueVisibility {
+        if clue.known {
+            return ClueVisibility::Everyone;
+        }
+        let mut players: Vec<_> = self
+            .0
+            .iter()
+            .filter(|(_, known)| known.contains(&clue.id))
+            .map(|(player, _)| player.clone())
+            .collect();
+        if players.is_empty() {
+            return ClueVisibility::Hidden;
+        }
+        players.sort();
+        ClueVisibility::Players(players)
+    }
+}
+
+/// Who a clue has been revealed to, see [`PlayerKnowledge::visibility`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClueVisibility {
+    /// Nobody knows the clue yet
+    Hidden,
+    /// The clue was only revealed to these players, sorted by id
+    Players(Vec<PersonId>),
+    /// The clue is [`known`](Clue::known) to everyone
+    Everyone,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(id: &str) -> PersonId {
+        serde_yaml::from_str(id).unwrap()
+    }
+
+    #[test]
+    fn reveal_to_one_player_only() {
+        let mut knowledge = PlayerKnowledge::default();
+        let knife = clue("[knife, A bloody knife, [], []]");
+        assert!(knowledge.reveal_to(&person("alice"), knife.id.clone()));
+        assert!(!knowledge.reveal_to(&person("alice"), knife.id.clone()));
+
+        assert!(knowledge.known_by(&person("alice")).contains(&knife.id));
+        assert!(!knowledge.known_by(&person("bob")).contains(&knife.id));
+        assert!(knowledge.can_see(&person("alice"), &knife));
+        assert!(!knowledge.can_see(&person("bob"), &knife));
+        assert_eq!(
+            knowledge.visibility(&knife),
+            ClueVisibility::Players(vec![person("alice")])
+        );
+    }
+
+    fn clue(yaml: &str) -> Clue {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn unknown_player_knows_nothing() {
+        let knowledge = PlayerKnowledge::default();
+        assert_eq!(knowledge.known_by(&person("nobody")).iter().count(), 0);
+    }
+
+    #[test]
+    fn globally_known_clues_are_visible_to_everyone() {
+        let knowledge = PlayerKnowledge::default();
+        let hidden = clue("[letter, A letter, [], []]");
+        let known =
+            clue("{id: note, information: A note, persons: [], locations: [], known: true}");
+        assert_eq!(knowledge.visibility(&hidden), ClueVisibility::Hidden);
+        assert_eq!(knowledge.visibility(&known), ClueVisibility::Everyone);
+        assert!(knowledge.can_see(&person("bob"), &known));
+    }
+}
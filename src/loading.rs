@@ -0,0 +1,188 @@
+//! Waiting for the data files to finish loading
+use bevy::{
+    asset::{Asset, AssetServer, HandleUntyped, LoadState},
+    prelude::{App, Assets, EventWriter, Handle, Plugin, Res, ResMut},
+};
+
+use crate::{clues::Clues, locations::Locations, persons::Persons};
+
+/// Event sent once every handle tracked by [`LoadingState`] has loaded,
+/// with how many clues, locations and persons were loaded in total
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetsLoaded {
+    /// The number of clues across the tracked [`Clues`] assets
+    pub clues: usize,
+    /// The number of locations across the tracked [`Locations`] assets
+    pub locations: usize,
+    /// The number of persons across the tracked [`Persons`] assets
+    pub persons: usize,
+}
+
+/// The asset handles to wait for before sending [`AssetsLoaded`], and how
+/// many of them have loaded so far
+#[derive(Debug, Default)]
+pub struct LoadingState {
+    handles: Vec<HandleUntyped>,
+    loaded: usize,
+    done: bool,
+}
+
+impl LoadingState {
+    /// Wait for a handle to load. Tracking a handle after [`AssetsLoaded`]
+    /// was sent means it will be sent again once the new handle loads.
+    pub fn track<T: Asset>(&mut self, handle: &Handle<T>) {
+        self.handles.push(handle.clone_untyped());
+        self.done = false;
+    }
+
+    /// Get how many of the tracked handles have loaded
+    pub fn loaded(&self) -> usize {
+        self.loaded
+    }
+
+    /// Get how many handles are tracked
+    pub fn total(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Check whether every tracked handle has loaded
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Bevy plugin to send [`AssetsLoaded`] once the handles in
+/// [`LoadingState`] have loaded. A handle that fails to load is never
+/// counted as loaded, so the event won't be sent.
+pub struct LoadingPlugin;
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingState>()
+            .add_event::<AssetsLoaded>()
+            .add_system(check_loading);
+    }
+}
+
+fn check_loading(
+    asset_server: Res<AssetServer>,
+    clues: Res<Assets<Clues>>,
+    locations: Res<Assets<Locations>>,
+    persons: Res<Assets<Persons>>,
+    mut state: ResMut<LoadingState>,
+    mut loaded: EventWriter<AssetsLoaded>,
+) {
+    if state.done || state.handles.is_empty() {
+        return;
+    }
+    state.loaded = state
+        .handles
+        .iter()
+        .filter(|h| asset_server.get_load_state(h.id) == LoadState::Loaded)
+        .count();
+    if state.loaded < state.handles.len() {
+        return;
+    }
+    state.done = true;
+
+    let mut counts = AssetsLoaded {
+        clues: 0,
+        locations: 0,
+        persons: 0,
+    };
+    for handle in &state.handles {
+        if let Some(c) = clues.get(&handle.typed_weak()) {
+            counts.clues += c.iter().count();
+        }
+        if let Some(l) = locations.get(&handle.typed_weak()) {
+            counts.locations += l.iter().count();
+        }
+        if let Some(p) = persons.get(&handle.typed_weak()) {
+            counts.persons += p.iter().count();
+        }
+    }
+    loaded.send(counts);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread, time::Duration};
+
+    use bevy::{
+        asset::{AssetPlugin, AssetServerSettings},
+        prelude::{Events, MinimalPlugins},
+    };
+
+    use super::*;
+    use crate::{
+        clues::CluesAssetPlugin, locations::LocationsAssetPlugin, persons::PersonsAssetPlugin,
+    };
+
+    #[test]
+    fn assets_loaded_is_sent_once_everything_has_loaded() {
+        let folder = std::env::temp_dir().join(format!("revealer-{}-loading", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(
+            folder.join("a.clues.yml"),
+            "clues:\n- [knife, x, [], []]\n- [cup, x, [], []]\n",
+        )
+        .unwrap();
+        fs::write(
+            folder.join("a.locations.yml"),
+            "locations:\n- {id: town, name: Town, parent_locations: [], info: null}\n",
+        )
+        .unwrap();
+        fs::write(folder.join("a.persons.yml"), "").unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(AssetServerSettings {
+            asset_folder: folder.to_string_lossy().into_owned(),
+            watch_for_changes: false,
+        })
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(CluesAssetPlugin::default())
+        .add_plugin(LocationsAssetPlugin::default())
+        .add_plugin(PersonsAssetPlugin::default())
+        .add_plugin(LoadingPlugin);
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        let clues: Handle<Clues> = asset_server.load("a.clues.yml");
+        let locations: Handle<Locations> = asset_server.load("a.locations.yml");
+        let persons: Handle<Persons> = asset_server.load("a.persons.yml");
+        let mut state = app.world.resource_mut::<LoadingState>();
+        state.track(&clues);
+        state.track(&locations);
+        state.track(&persons);
+
+        let mut reader = app.world.resource::<Events<AssetsLoaded>>().get_reader();
+        let mut sent = Vec::new();
+        for _ in 0..500 {
+            app.update();
+            sent.extend(
+                reader
+                    .iter(app.world.resource::<Events<AssetsLoaded>>())
+                    .copied(),
+            );
+            if app.world.resource::<LoadingState>().is_done() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        app.update();
+        sent.extend(
+            reader
+                .iter(app.world.resource::<Events<AssetsLoaded>>())
+                .copied(),
+        );
+        fs::remove_dir_all(folder).unwrap();
+
+        assert_eq!(app.world.resource::<LoadingState>().loaded(), 3);
+        assert_eq!(
+            sent,
+            [AssetsLoaded {
+                clues: 2,
+                locations: 1,
+                persons: 0,
+            }]
+        );
+    }
+}
@@ -1,14 +1,62 @@
 //! Locations and the Asset loader for them
-use bevy::{reflect::TypeUuid, utils::HashMap};
-use serde::Deserialize;
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+    fmt,
+    io::Read,
+};
+
+use bevy::{
+    asset::HandleId,
+    math::Vec2,
+    prelude::{AssetEvent, EventReader, Handle, ResMut},
+    reflect::{FromReflect, Reflect, TypeUuid},
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+pub use assets::LocationsAssetPlugin;
+
+use crate::{
+    clues::{ClueError, Clues},
+    collation::compare_names,
+    maps::MapId,
+    yaml::{self, list_or_null},
+};
 
 /// A wrapper around a string to represent a location
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize)]
-#[serde(transparent)]
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[cfg_attr(not(feature = "strict-ids"), serde(transparent))]
+#[cfg_attr(feature = "strict-ids", serde(try_from = "String", into = "String"))]
 pub struct LocationId(String);
 
+#[cfg(feature = "strict-ids")]
+impl TryFrom<String> for LocationId {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        crate::validation::check_id(id).map(Self)
+    }
+}
+
+#[cfg(feature = "strict-ids")]
+impl From<LocationId> for String {
+    fn from(id: LocationId) -> Self {
+        id.0
+    }
+}
+
+impl Borrow<str> for LocationId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
 /// A location
-#[derive(Debug)]
+#[derive(Debug, Reflect)]
 pub struct Location {
     /// The id of the location
     pub id: LocationId,
@@ -18,10 +66,89 @@ pub struct Location {
     pub parent_locations: Vec<LocationId>,
     /// Locations that this location is a parent of.
     pub children_locations: Vec<LocationId>,
+    /// Locations that are connected to this one without containing it or
+    /// being contained by it, e.g. two towns joined by a road.
+    /// Adjacency is always symmetric.
+    pub adjacent_locations: Vec<LocationId>,
+    /// How long it takes to travel to each of the adjacent locations.
+    /// Edges missing from here take [`DEFAULT_TRAVEL_TIME`].
+    pub travel_times: HashMap<LocationId, f32>,
+    /// The map the location is on, if it's only on one
+    pub map: Option<MapId>,
+    /// Tags to categorise the location, e.g. `"urban"` or `"dungeon"`
+    pub tags: Vec<String>,
+    /// Named groupings the location belongs to, e.g. `"cult territory"`.
+    /// Unlike the parents these don't have to follow the hierarchy and a
+    /// location can be in any number of them.
+    pub regions: Vec<String>,
+    /// The outline of the location on its map, if it covers an area rather
+    /// than being a single point
+    pub area: Option<Vec<Vec2>>,
     /// A description of the location (intended for public knowledge)
     pub info: Option<String>,
 }
 
+/// The travel time of an adjacency edge that doesn't specify one
+pub const DEFAULT_TRAVEL_TIME: f32 = 1.0;
+
+impl Location {
+    /// Get how long it takes to travel from this location to an adjacent one
+    pub fn travel_time_to(&self, id: &LocationId) -> f32 {
+        self.travel_times
+            .get(id)
+            .copied()
+            .unwrap_or(DEFAULT_TRAVEL_TIME)
+    }
+
+    /// Check whether a point on the map is inside the [`Location::area`].
+    /// Always `false` for locations without an area, or with fewer than
+    /// three points in it.
+    pub fn contains_point(&self, p: Vec2) -> bool {
+        let area = match &self.area {
+            Some(area) if area.len() >= 3 => area,
+            _ => return false,
+        };
+        // Cast a ray to the right of the point and count the edges it
+        // crosses, an odd number means it's inside
+        let mut inside = false;
+        let mut previous = area[area.len() - 1];
+        for &current in area {
+            if (current.y > p.y) != (previous.y > p.y) {
+                let x = current.x
+                    + (p.y - current.y) * (previous.x - current.x) / (previous.y - current.y);
+                if p.x < x {
+                    inside = !inside;
+                }
+            }
+            previous = current;
+        }
+        inside
+    }
+
+    /// Get the [`Location::info`] with every `{{name}}` in it replaced by
+    /// the campaign variable `name`, e.g. `{{ruler}}`. Spaces around the
+    /// name are ignored. Variables missing from `vars` are left in as
+    /// written, so they are easy to spot.
+    pub fn rendered_info(&self, vars: &HashMap<String, String>) -> Option<String> {
+        let mut info = self.info.as_deref()?;
+        let mut rendered = String::with_capacity(info.len());
+        while let Some(start) = info.find("{{") {
+            let end = match info[start + 2..].find("}}") {
+                Some(end) => start + 2 + end,
+                None => break,
+            };
+            rendered.push_str(&info[..start]);
+            match vars.get(info[start + 2..end].trim()) {
+                Some(value) => rendered.push_str(value),
+                None => rendered.push_str(&info[start..end + 2]),
+            }
+            info = &info[end + 2..];
+        }
+        rendered.push_str(info);
+        Some(rendered)
+    }
+}
+
 /// Same as location, but with some fields missing.
 /// We can't derserialize to a [`Location`] directly as the
 /// child locations have to be computed.
@@ -33,33 +160,184 @@ struct LocationDeser {
     pub name: String,
     /// Locations that are a parent of this one.
     pub parent_locations: Vec<LocationId>,
+    /// Locations that are connected to this one
+    #[serde(default)]
+    pub adjacent_locations: Vec<LocationId>,
+    /// How long it takes to travel to adjacent locations
+    #[serde(default)]
+    pub travel_times: HashMap<LocationId, f32>,
+    /// The map the location is on
+    #[serde(default)]
+    pub map: Option<MapId>,
+    /// Tags to categorise the location
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Regions the location belongs to
+    #[serde(default)]
+    pub regions: Vec<String>,
+    /// The outline of the location, as a list of `[x, y]` points
+    #[serde(default)]
+    pub area: Option<Vec<Vec2>>,
     /// A description of the location (intended for public knowledge)
     pub info: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct LocationsFile {
+    #[serde(default, deserialize_with = "list_or_null")]
     locations: Vec<LocationDeser>,
 }
 
+/// Errors that can happen when loading locations
+#[derive(Debug)]
+pub enum LocationError {
+    /// A location lists a parent that isn't in the file
+    MissingParent {
+        /// The location listing the parent
+        location: LocationId,
+        /// The parent that doesn't exist
+        parent: LocationId,
+    },
+    /// More than one of the other errors, e.g. every missing parent in a
+    /// file
+    Multiple(Vec<LocationError>),
+}
+
+impl fmt::Display for LocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocationError::MissingParent { location, parent } => write!(
+                f,
+                "location {:?} has missing parent {:?}",
+                location.0, parent.0
+            ),
+            LocationError::Multiple(errors) => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocationError {}
+
 /// A holder for many locations
 #[derive(Debug, Default, TypeUuid)]
 #[uuid = "9d69dd7b-8fbb-460d-bd7c-10a71f87c2b4"]
 pub struct Locations {
     locations: HashMap<LocationId, Location>,
+    by_tag: HashMap<String, Vec<LocationId>>,
+    by_region: HashMap<String, Vec<LocationId>>,
+    revision: u64,
+}
+
+impl LocationsFile {
+    /// Find every parent listed in the file that isn't in it
+    fn missing_parents(&self) -> Vec<LocationError> {
+        let ids: HashSet<&LocationId> = self.locations.iter().map(|l| &l.id).collect();
+        self.locations
+            .iter()
+            .flat_map(|location| {
+                location
+                    .parent_locations
+                    .iter()
+                    .filter(|p| !ids.contains(p))
+                    .map(|parent| LocationError::MissingParent {
+                        location: location.id.clone(),
+                        parent: parent.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Check every parent listed in the file is in it, failing with all
+    /// the [`LocationsFile::missing_parents`] if not
+    fn check_parents(&self) -> Result<(), LocationError> {
+        let mut errors = self.missing_parents();
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(LocationError::Multiple(errors)),
+        }
+    }
+}
+
+impl TryFrom<LocationsFile> for Locations {
+    type Error = LocationError;
+
+    fn try_from(file: LocationsFile) -> Result<Self, Self::Error> {
+        // Check all the parents exist before linking anything
+        file.check_parents()?;
+        Ok(Self::link(file))
+    }
+}
+
+impl Locations {
+    /// Load locations from a locations file being read from a [`Read`]er,
+    /// e.g. a socket, rather than a buffer
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, ClueError> {
+        let file: LocationsFile = yaml::from_reader(reader)?;
+        Ok(file.try_into()?)
+    }
+
+    /// Load only the locations on one map from a locations file, plus those
+    /// that aren't on any map. Parents on other maps are left unresolved
+    /// rather than being an error, call this again with another [`MapId`]
+    /// to load that map's locations when they're needed.
+    pub fn from_yaml_for_map(bytes: &[u8], map: &MapId) -> Result<Self, ClueError> {
+        let file: LocationsFile = yaml::from_slice(bytes)?;
+        Ok(Self::for_map(file, map)?)
+    }
+
+    fn for_map(mut file: LocationsFile, map: &MapId) -> Result<Self, LocationError> {
+        // Parents have to exist somewhere in the file, even if not on this map
+        file.check_parents()?;
+        file.locations
+            .retain(|l| l.map.as_ref().is_none_or(|m| m == map));
+        Ok(Self::link(file))
+    }
+}
+
+/// Load several locations files as if they were one, so parents can be in
+/// a different file to their children. Every problem found is returned
+/// along with whatever locations could be loaded.
+pub(crate) fn load_files(files: &[Vec<u8>]) -> (Locations, Vec<ClueError>) {
+    let mut errors = Vec::new();
+    let mut combined = LocationsFile::default();
+    for bytes in files {
+        match yaml::from_slice::<LocationsFile>(bytes) {
+            Ok(file) => combined.locations.extend(file.locations),
+            Err(e) => errors.push(e.into()),
+        }
+    }
+    errors.extend(combined.missing_parents().into_iter().map(ClueError::from));
+    (Locations::link(combined), errors)
 }
 
-impl From<LocationsFile> for Locations {
-    fn from(file: LocationsFile) -> Self {
+impl Locations {
+    /// Build the locations from a file, linking up children and adjacency.
+    /// Missing parents are left unlinked.
+    fn link(file: LocationsFile) -> Self {
         let mut locations = Self::new();
 
-        // First add all the locations
+        // Add all the locations
         for location in file.locations {
             let loc = Location {
                 id: location.id.clone(),
                 name: location.name,
                 parent_locations: location.parent_locations,
                 children_locations: Vec::new(),
+                adjacent_locations: location.adjacent_locations,
+                travel_times: location.travel_times,
+                map: location.map,
+                tags: location.tags,
+                regions: location.regions,
+                area: location.area,
                 info: location.info,
             };
 
@@ -82,11 +360,63 @@ impl From<LocationsFile> for Locations {
 
         // Then we can go through and add the children
         for (id, location) in locations.locations.iter_mut() {
-            location.children_locations = children_locations.remove(id).unwrap();
+            location.children_locations = children_locations.remove(id).unwrap_or_default();
+        }
+
+        // Make adjacency symmetric by adding any missing reverse edges
+        let mut reverse_edges = Vec::new();
+        for (id, location) in &locations.locations {
+            for adjacent_id in &location.adjacent_locations {
+                if let Some(adjacent) = locations.locations.get(adjacent_id) {
+                    if !adjacent.adjacent_locations.contains(id) {
+                        reverse_edges.push((adjacent_id.clone(), id.clone(), None));
+                    }
+                    if let Some(time) = location.travel_times.get(adjacent_id) {
+                        if !adjacent.travel_times.contains_key(id) {
+                            reverse_edges.push((adjacent_id.clone(), id.clone(), Some(*time)));
+                        }
+                    }
+                }
+            }
+        }
+        for (id, adjacent_id, time) in reverse_edges {
+            if let Some(location) = locations.locations.get_mut(&id) {
+                if let Some(time) = time {
+                    location.travel_times.insert(adjacent_id.clone(), time);
+                }
+                if !location.adjacent_locations.contains(&adjacent_id) {
+                    location.adjacent_locations.push(adjacent_id);
+                }
+            }
         }
 
+        locations.reindex();
         locations
     }
+
+    /// Rebuild the tag and region indices from scratch
+    fn reindex(&mut self) {
+        self.by_tag.clear();
+        self.by_region.clear();
+        for (id, location) in &self.locations {
+            for tag in &location.tags {
+                match self.by_tag.get_mut(tag) {
+                    Some(l) => l.push(id.clone()),
+                    None => {
+                        self.by_tag.insert(tag.clone(), vec![id.clone()]);
+                    }
+                }
+            }
+            for region in &location.regions {
+                match self.by_region.get_mut(region) {
+                    Some(l) => l.push(id.clone()),
+                    None => {
+                        self.by_region.insert(region.clone(), vec![id.clone()]);
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl Locations {
@@ -102,9 +432,88 @@ impl Locations {
 
     /// Get a [`Option`]al mutable reference to a [`Location`]
     pub fn get_mut(&mut self, id: &LocationId) -> Option<&mut Location> {
+        // The location could be changed through the reference, so cached
+        // hierarchy lookups can't be trusted any more
+        self.revision += 1;
         self.locations.get_mut(id)
     }
 
+    /// Get a counter that goes up whenever the locations might have been
+    /// changed, so caches like [`LocationTreeCache`] know when they are
+    /// out of date
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Drop all the locations on maps other than `map`, to save memory when
+    /// only one map is displayed. Locations that aren't on any map are kept.
+    /// Links to the dropped locations are left unresolved, so they are
+    /// skipped by [`Locations::iter_parents`] and the like.
+    pub fn retain_map(&mut self, map: &MapId) {
+        self.locations
+            .retain(|_, l| l.map.as_ref().is_none_or(|m| m == map));
+        self.revision += 1;
+        self.reindex();
+    }
+
+    /// Keep only the locations `f` returns `true` for. Unlike
+    /// [`Locations::retain_map`] links to the removed locations are dropped
+    /// from the ones that are kept, so no id is left dangling.
+    pub fn retain<F: FnMut(&Location) -> bool>(&mut self, mut f: F) {
+        self.locations.retain(|_, l| f(l));
+        let kept: HashSet<_> = self.locations.keys().cloned().collect();
+        for location in self.locations.values_mut() {
+            location.parent_locations.retain(|id| kept.contains(id));
+            location.children_locations.retain(|id| kept.contains(id));
+            location.adjacent_locations.retain(|id| kept.contains(id));
+            location.travel_times.retain(|id, _| kept.contains(id));
+        }
+        self.revision += 1;
+        self.reindex();
+    }
+
+    /// Get an [`Iterator`] over all the [`Location`]s
+    pub fn iter(&self) -> impl Iterator<Item = &Location> {
+        self.locations.values()
+    }
+
+    /// Check whether a [`Location`] ends up being one of its own parents
+    pub(crate) fn is_own_ancestor(&self, id: &LocationId) -> bool {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<_> = self.iter_parents(id).collect();
+        while let Some(location) = queue.pop_front() {
+            if &location.id == id {
+                return true;
+            }
+            if seen.insert(&location.id) {
+                queue.extend(self.iter_parents(&location.id));
+            }
+        }
+        false
+    }
+
+    /// Get an [`Iterator`] over all the [`Location`]s with a tag. The tags
+    /// are indexed when loading, so changes made through
+    /// [`Locations::get_mut`] aren't picked up.
+    pub fn iter_by_tag(&self, tag: &str) -> impl Iterator<Item = &Location> {
+        self.by_tag
+            .get(tag)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.get(id))
+    }
+
+    /// Get an [`Iterator`] over all the [`Location`]s in a region, see
+    /// [`Location::regions`]. Like the tags, regions are indexed when
+    /// loading.
+    pub fn iter_in_region(&self, region: &str) -> impl Iterator<Item = &Location> {
+        self.by_region
+            .get(region)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.get(id))
+    }
+
     /// Get an [`Iterator`] over the parents of a [`Location`] from its [`LocationId`]
     pub fn iter_parents(&self, id: &LocationId) -> impl Iterator<Item = &Location> {
         self.locations
@@ -122,26 +531,531 @@ impl Locations {
             .flat_map(|l| &l.children_locations)
             .filter_map(|l_id| self.get(l_id))
     }
+
+    /// Get an [`Iterator`] over the locations adjacent to a [`Location`] from its [`LocationId`]
+    pub fn iter_adjacent(&self, id: &LocationId) -> impl Iterator<Item = &Location> {
+        self.locations
+            .get(id)
+            .into_iter()
+            .flat_map(|l| &l.adjacent_locations)
+            .filter_map(|l_id| self.get(l_id))
+    }
+
+    /// Find the quickest route between two [`Location`]s over their
+    /// adjacency, returning the ids along the route (including both ends)
+    /// and the total travel time, or [`None`] if there is no route
+    pub fn travel_route(
+        &self,
+        from: &LocationId,
+        to: &LocationId,
+    ) -> Option<(Vec<LocationId>, f32)> {
+        self.locations.get(from)?;
+        let mut costs: HashMap<&LocationId, f32> = HashMap::new();
+        let mut previous: HashMap<&LocationId, &LocationId> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        costs.insert(from, 0.0);
+        queue.push(RouteStep {
+            cost: 0.0,
+            id: from,
+        });
+
+        while let Some(RouteStep { cost, id }) = queue.pop() {
+            if id == to {
+                let mut route = vec![id.clone()];
+                let mut current = id;
+                while let Some(prev) = previous.get(current) {
+                    route.push((*prev).clone());
+                    current = prev;
+                }
+                route.reverse();
+                return Some((route, cost));
+            }
+            if costs.get(id).is_some_and(|c| cost > *c) {
+                continue;
+            }
+            let location = &self.locations[id];
+            for adjacent in self.iter_adjacent(id) {
+                let next_cost = cost + location.travel_time_to(&adjacent.id);
+                if costs.get(&adjacent.id).is_none_or(|c| next_cost < *c) {
+                    costs.insert(&adjacent.id, next_cost);
+                    previous.insert(&adjacent.id, id);
+                    queue.push(RouteStep {
+                        cost: next_cost,
+                        id: &adjacent.id,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Get an [`Iterator`] over all the ancestors of a [`Location`], nearest
+    /// first, not including the location itself
+    pub fn iter_ancestors(&self, id: &LocationId) -> impl Iterator<Item = &Location> {
+        let mut ancestors = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<_> = self.iter_parents(id).collect();
+        while let Some(location) = queue.pop_front() {
+            if &location.id != id && seen.insert(&location.id) {
+                ancestors.push(location);
+                queue.extend(self.iter_parents(&location.id));
+            }
+        }
+        ancestors.into_iter()
+    }
+
+    /// Check whether a [`Location`] is inside another, directly or not.
+    /// A location is never its own ancestor here, even in a cycle.
+    pub fn is_ancestor_of(&self, ancestor: &LocationId, descendant: &LocationId) -> bool {
+        ancestor != descendant && self.iter_ancestors(descendant).any(|l| &l.id == ancestor)
+    }
+
+    /// Get an [`Iterator`] over the locations sharing at least one parent
+    /// with a [`Location`], not including the location itself
+    pub fn iter_siblings<'a>(&'a self, id: &'a LocationId) -> impl Iterator<Item = &'a Location> {
+        let mut seen = HashSet::new();
+        self.iter_parents(id)
+            .flat_map(|parent| self.iter_children(&parent.id))
+            .filter(move |l| &l.id != id && seen.insert(&l.id))
+    }
+
+    /// Visit a [`Location`] and all its descendants level by level, calling
+    /// `visit` with each location and how far below `root` it is. Each
+    /// location is only visited once, at the depth it was first reached.
+    pub fn bfs_from<F: FnMut(&Location, usize)>(&self, root: &LocationId, mut visit: F) {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<_> = self.get(root).map(|l| (l, 0)).into_iter().collect();
+        while let Some((location, depth)) = queue.pop_front() {
+            if !seen.insert(&location.id) {
+                continue;
+            }
+            visit(location, depth);
+            queue.extend(self.iter_children(&location.id).map(|c| (c, depth + 1)));
+        }
+    }
+
+    /// Get the ids of all the descendants of a [`Location`], not including
+    /// the location itself
+    pub fn descendant_ids(&self, id: &LocationId) -> HashSet<LocationId> {
+        let mut descendants = HashSet::new();
+        let mut queue: VecDeque<_> = self.iter_children(id).collect();
+        while let Some(location) = queue.pop_front() {
+            if &location.id != id && descendants.insert(location.id.clone()) {
+                queue.extend(self.iter_children(&location.id));
+            }
+        }
+        descendants
+    }
+
+    /// Get an [`Iterator`] over all the [`Location`]s sorted by their name.
+    ///
+    /// Names are compared with their accents removed and case folded,
+    /// so `"Émile"` sorts alongside `"emile"` rather than after `"Zoé"`.
+    pub fn iter_sorted_by_name(&self) -> impl Iterator<Item = &Location> {
+        let mut locations: Vec<_> = self.locations.values().collect();
+        locations.sort_by(|a, b| compare_names(&a.name, &b.name));
+        locations.into_iter()
+    }
+
+    /// Write out the location hierarchy as GraphML, e.g. to open in yEd.
+    /// Each location is a node labelled with its name, with edges going
+    /// from parents to their children.
+    pub fn to_graphml(&self) -> String {
+        fn escape(text: &str) -> String {
+            text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+                .replace('\'', "&apos;")
+        }
+
+        let mut locations: Vec<_> = self.locations.values().collect();
+        locations.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut graphml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <graph id=\"locations\" edgedefault=\"directed\">\n",
+        ));
+        for location in &locations {
+            graphml.push_str(&format!(
+                "    <node id=\"{}\">\n      <data key=\"label\">{}</data>\n    </node>\n",
+                escape(&location.id.0),
+                escape(&location.name)
+            ));
+        }
+        for location in &locations {
+            let mut children: Vec<_> = location
+                .children_locations
+                .iter()
+                .filter(|c| self.locations.contains_key(*c))
+                .collect();
+            children.sort();
+            for child in children {
+                graphml.push_str(&format!(
+                    "    <edge source=\"{}\" target=\"{}\"/>\n",
+                    escape(&location.id.0),
+                    escape(&child.0)
+                ));
+            }
+        }
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+
+    /// Find the locations whose links disagree with the other end, a child
+    /// that doesn't list the location as a parent or a parent that doesn't
+    /// list it as a child, e.g. after editing them with
+    /// [`Locations::get_mut`]. Links to locations that don't exist are
+    /// ignored. The ids are sorted.
+    pub fn check_consistency(&self) -> Vec<LocationId> {
+        let mut inconsistent: Vec<_> = self
+            .locations
+            .values()
+            .filter(|location| {
+                let bad_child = location.children_locations.iter().any(|c| {
+                    self.locations
+                        .get(c)
+                        .is_some_and(|c| !c.parent_locations.contains(&location.id))
+                });
+                let bad_parent = location.parent_locations.iter().any(|p| {
+                    self.locations
+                        .get(p)
+                        .is_some_and(|p| !p.children_locations.contains(&location.id))
+                });
+                bad_child || bad_parent
+            })
+            .map(|l| l.id.clone())
+            .collect();
+        inconsistent.sort();
+        inconsistent
+    }
+
+    /// Get an [`Iterator`] over the [`Location`]s that no clue references
+    pub fn without_clues<'a>(&'a self, clues: &'a Clues) -> impl Iterator<Item = &'a Location> {
+        self.locations
+            .values()
+            .filter(|l| clues.get_by_location(&l.id).next().is_none())
+    }
+
+    /// Get an [`Iterator`] over the [`Location`]s that have parents, but
+    /// none of which exist, meaning they can't be reached from any root.
+    ///
+    /// Loading a file with missing parents fails, so these only come from
+    /// pruning: [`Locations::retain_map`] and [`Locations::from_yaml_for_map`]
+    /// leave links to locations on other maps unresolved, and parents can
+    /// be edited through [`Locations::get_mut`]. Use this to find the
+    /// locations that lost their place in the hierarchy that way.
+    pub fn orphans(&self) -> impl Iterator<Item = &Location> {
+        self.locations.values().filter(|l| {
+            !l.parent_locations.is_empty()
+                && l.parent_locations
+                    .iter()
+                    .all(|p_id| !self.locations.contains_key(p_id))
+        })
+    }
+}
+
+/// An entry in the [`Locations::travel_route`] queue, ordered so the
+/// cheapest step is popped first
+struct RouteStep<'a> {
+    cost: f32,
+    id: &'a LocationId,
+}
+
+impl PartialEq for RouteStep<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RouteStep<'_> {}
+
+impl PartialOrd for RouteStep<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RouteStep<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| self.id.cmp(other.id))
+    }
+}
+
+/// Merge two [`Location`]s that turn out to be the same place. Everything
+/// linked to `drop`, its parents, children, adjacent locations and the
+/// clues referencing it, is relinked to `keep`, then `drop` is removed.
+/// Nothing happens if either location doesn't exist or they're the same.
+pub fn merge_locations(
+    locations: &mut Locations,
+    clues: &mut Clues,
+    keep: &LocationId,
+    drop: &LocationId,
+) {
+    if keep == drop || locations.get(keep).is_none() {
+        return;
+    }
+    let dropped = match locations.locations.remove(drop) {
+        Some(dropped) => dropped,
+        None => return,
+    };
+    locations.revision += 1;
+
+    // Replace `drop` with `keep` in a list of links, without duplicating
+    // links or linking a location to itself
+    let relink = |ids: &mut Vec<LocationId>, owner: &LocationId| {
+        for id in ids.iter_mut().filter(|id| *id == drop) {
+            *id = keep.clone();
+        }
+        let mut seen = HashSet::new();
+        ids.retain(|id| id != owner && seen.insert(id.clone()));
+    };
+
+    for location in locations.locations.values_mut() {
+        relink(&mut location.parent_locations, &location.id);
+        relink(&mut location.children_locations, &location.id);
+        relink(&mut location.adjacent_locations, &location.id);
+        if let Some(time) = location.travel_times.remove(drop) {
+            if &location.id != keep {
+                location.travel_times.entry(keep.clone()).or_insert(time);
+            }
+        }
+    }
+
+    let kept = locations.locations.get_mut(keep).expect("checked above");
+    kept.parent_locations.extend(dropped.parent_locations);
+    kept.children_locations.extend(dropped.children_locations);
+    kept.adjacent_locations.extend(dropped.adjacent_locations);
+    relink(&mut kept.parent_locations, keep);
+    relink(&mut kept.children_locations, keep);
+    relink(&mut kept.adjacent_locations, keep);
+    for (id, time) in dropped.travel_times {
+        if &id != keep && &id != drop {
+            kept.travel_times.entry(id).or_insert(time);
+        }
+    }
+    if kept.info.is_none() {
+        kept.info = dropped.info;
+    }
+    if kept.area.is_none() {
+        kept.area = dropped.area;
+    }
+    for tag in dropped.tags {
+        if !kept.tags.contains(&tag) {
+            kept.tags.push(tag);
+        }
+    }
+    for region in dropped.regions {
+        if !kept.regions.contains(&region) {
+            kept.regions.push(region);
+        }
+    }
+    locations.reindex();
+
+    clues.replace_location(drop, keep);
+}
+
+/// The descendants cached for one [`Locations`] asset
+#[derive(Debug, Default)]
+struct CachedTree {
+    /// The [`Locations::revision`] the descendants were computed at
+    revision: u64,
+    descendants: HashMap<LocationId, HashSet<LocationId>>,
+}
+
+/// Caches the descendants of [`Location`]s so repeated tree queries don't
+/// have to walk the hierarchy each time. Each [`Locations`] asset is
+/// cached separately, and its entries are dropped when the asset is
+/// created, modified or removed, or when its [`Locations::revision`]
+/// changes.
+#[derive(Debug, Default)]
+pub struct LocationTreeCache {
+    trees: HashMap<HandleId, CachedTree>,
+}
+
+impl LocationTreeCache {
+    /// Get the ids of all the descendants of a [`Location`] in the asset
+    /// `handle` points to, computing them with
+    /// [`Locations::descendant_ids`] if they aren't cached
+    pub fn descendants(
+        &mut self,
+        handle: &Handle<Locations>,
+        locations: &Locations,
+        id: &LocationId,
+    ) -> &HashSet<LocationId> {
+        let tree = self.trees.entry(handle.id).or_default();
+        if tree.revision != locations.revision {
+            tree.descendants.clear();
+            tree.revision = locations.revision;
+        }
+        tree.descendants
+            .entry(id.clone())
+            .or_insert_with(|| locations.descendant_ids(id))
+    }
+
+    /// Remove everything cached for one [`Locations`] asset
+    pub fn invalidate(&mut self, handle: &Handle<Locations>) {
+        self.trees.remove(&handle.id);
+    }
+
+    /// Remove everything from the cache
+    pub fn clear(&mut self) {
+        self.trees.clear();
+    }
+}
+
+fn invalidate_location_tree_cache(
+    mut events: EventReader<AssetEvent<Locations>>,
+    mut cache: ResMut<LocationTreeCache>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle }
+            | AssetEvent::Modified { handle }
+            | AssetEvent::Removed { handle } => cache.invalidate(handle),
+        }
+    }
 }
 
 mod assets {
+    use std::{
+        path::PathBuf,
+        sync::{Arc, RwLock},
+    };
+
     use bevy::{
         asset::{AssetLoader, LoadedAsset},
-        prelude::{AddAsset, Plugin},
+        prelude::{AddAsset, AssetServer, Assets, Plugin, Res},
+        utils::HashMap,
+    };
+
+    use super::{
+        invalidate_location_tree_cache, yaml, Location, LocationId, LocationTreeCache, Locations,
+        LocationsFile,
     };
 
-    use super::{Locations, LocationsFile};
+    use crate::{
+        extensions::AssetExtensions,
+        maps::{ActiveMap, MapId},
+        validation::TextValidation,
+    };
 
     /// Bevy plugin to load a locations file
-    pub struct LocationsAssetPlugin;
+    pub struct LocationsAssetPlugin {
+        extensions: AssetExtensions,
+        text_validation: Option<TextValidation>,
+        only_active_map: bool,
+    }
+
+    impl Default for LocationsAssetPlugin {
+        fn default() -> Self {
+            Self {
+                extensions: AssetExtensions::new("locations.yml"),
+                text_validation: None,
+                only_active_map: false,
+            }
+        }
+    }
+
+    impl LocationsAssetPlugin {
+        /// Load locations from files with any of the given extensions instead
+        /// of the default `locations.yml`. Extensions are given without a
+        /// leading `.`, e.g. `locations.yaml`.
+        /// An empty list keeps the default.
+        pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+            self.extensions.set(extensions);
+            self
+        }
+
+        /// Warn about suspicious [`Location::info`] as locations are loaded
+        pub fn with_text_validation(mut self, validation: TextValidation) -> Self {
+            self.text_validation = Some(validation);
+            self
+        }
+
+        /// Only load the locations on the [`ActiveMap`], plus those that
+        /// aren't on any map, like [`Locations::from_yaml_for_map`].
+        /// Everything is loaded while there's no active map, and loaded
+        /// locations are reloaded whenever it changes.
+        pub fn only_active_map(mut self) -> Self {
+            self.only_active_map = true;
+            self
+        }
+    }
+
+    /// The map locations are being loaded for and the map each file was
+    /// last loaded for, shared with the loader since it runs off the main
+    /// thread
+    #[derive(Clone, Default)]
+    struct MapFilter(Arc<RwLock<LoadedMaps>>);
+
+    #[derive(Default)]
+    struct LoadedMaps {
+        active: Option<MapId>,
+        files: HashMap<PathBuf, Option<MapId>>,
+    }
+
+    /// Reload any locations asset that wasn't loaded for the [`ActiveMap`],
+    /// either because it changed or because the asset started loading
+    /// before it was set
+    fn reload_for_active_map(
+        active: Option<Res<ActiveMap>>,
+        filter: Res<MapFilter>,
+        locations: Res<Assets<Locations>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        let mut loaded = filter.0.write().unwrap();
+        if let Some(active) = active.filter(|a| a.is_changed()) {
+            loaded.active = active.0.clone();
+        }
+        for (handle, _) in locations.iter() {
+            let path = match asset_server.get_handle_path(handle) {
+                Some(path) => path,
+                None => continue,
+            };
+            let active = loaded.active.clone();
+            let map = loaded.files.entry(path.path().to_owned()).or_default();
+            if *map != active {
+                // Don't reload again while it's loading
+                *map = active;
+                asset_server.reload_asset(path);
+            }
+        }
+    }
+
     impl Plugin for LocationsAssetPlugin {
         fn build(&self, app: &mut bevy::prelude::App) {
             app.add_asset::<Locations>()
-                .add_asset_loader(LocationsAssetLoader);
+                .register_type::<Location>()
+                .register_type::<LocationId>()
+                .init_resource::<LocationTreeCache>()
+                .add_system(invalidate_location_tree_cache);
+            let map = if self.only_active_map {
+                let map = MapFilter::default();
+                app.insert_resource(map.clone())
+                    .add_system(reload_for_active_map);
+                Some(map)
+            } else {
+                None
+            };
+            app.add_asset_loader(LocationsAssetLoader {
+                extensions: self.extensions.get(),
+                text_validation: self.text_validation,
+                map,
+            });
         }
     }
 
-    struct LocationsAssetLoader;
+    struct LocationsAssetLoader {
+        extensions: Vec<&'static str>,
+        text_validation: Option<TextValidation>,
+        map: Option<MapFilter>,
+    }
     impl AssetLoader for LocationsAssetLoader {
         fn load<'a>(
             &'a self,
@@ -149,15 +1063,519 @@ mod assets {
             load_context: &'a mut bevy::asset::LoadContext,
         ) -> bevy::utils::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
             Box::pin(async move {
-                let locations_file: LocationsFile = serde_yaml::from_slice(bytes)?;
-                let locations: Locations = locations_file.into();
+                let locations_file: LocationsFile = yaml::from_slice(bytes)?;
+                if let Some(validation) = &self.text_validation {
+                    for location in &locations_file.locations {
+                        if let Some(info) = &location.info {
+                            validation.check("location", &location.id.0, info);
+                        }
+                    }
+                }
+                let map = self.map.as_ref().and_then(|filter| {
+                    let mut loaded = filter.0.write().unwrap();
+                    let active = loaded.active.clone();
+                    loaded
+                        .files
+                        .insert(load_context.path().to_owned(), active.clone());
+                    active
+                });
+                let locations = match map {
+                    Some(map) => Locations::for_map(locations_file, &map)?,
+                    None => locations_file.try_into()?,
+                };
                 load_context.set_default_asset(LoadedAsset::new(locations));
                 Ok(())
             })
         }
 
         fn extensions(&self) -> &[&str] {
-            &[".locations.yml"]
+            &self.extensions
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread, time::Duration};
+
+    use bevy::{
+        asset::{AssetPlugin, AssetServerSettings, HandleId},
+        prelude::{App, AssetServer, Assets, MinimalPlugins, Mut},
+    };
+
+    use super::*;
+    use crate::maps::ActiveMap;
+
+    fn locations(yaml: &str) -> Locations {
+        serde_yaml::from_str::<LocationsFile>(yaml)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn id(id: &str) -> LocationId {
+        LocationId(id.to_string())
+    }
+
+    #[test]
+    fn locations_without_children_load() {
+        let locations =
+            locations("locations:\n- {id: town, name: Town, parent_locations: [], info: null}\n");
+        assert!(locations
+            .get(&id("town"))
+            .unwrap()
+            .children_locations
+            .is_empty());
+    }
+
+    const TOWN: &str = "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town], info: null}
+- {id: cellar, name: Cellar, parent_locations: [inn], info: null}
+- {id: well, name: Well, parent_locations: [town], info: null}
+";
+
+    #[test]
+    fn orphans_have_only_missing_parents() {
+        let mut locations = locations(TOWN);
+        locations.get_mut(&id("inn")).unwrap().parent_locations = vec![id("gone")];
+        let orphans: Vec<_> = locations.orphans().map(|l| l.id.0.as_str()).collect();
+        assert_eq!(orphans, ["inn"]);
+    }
+
+    fn sorted(ids: &HashSet<LocationId>) -> Vec<&str> {
+        let mut ids: Vec<_> = ids.iter().map(|l| l.0.as_str()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn tree_cache_matches_uncached_descendants() {
+        let locations = locations(TOWN);
+        let handle = Handle::weak(HandleId::random::<Locations>());
+        let mut cache = LocationTreeCache::default();
+        for location in ["town", "inn", "cellar"] {
+            assert_eq!(
+                cache.descendants(&handle, &locations, &id(location)),
+                &locations.descendant_ids(&id(location))
+            );
         }
+        assert_eq!(
+            sorted(cache.descendants(&handle, &locations, &id("town"))),
+            ["cellar", "inn", "well"]
+        );
+    }
+
+    #[test]
+    fn tree_cache_is_cleared_on_asset_events() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_plugin(LocationsAssetPlugin::default());
+        let handle = app
+            .world
+            .resource_mut::<Assets<Locations>>()
+            .add(locations(TOWN));
+        app.update();
+        let cached = |app: &mut App| {
+            app.world
+                .resource_scope(|world, mut cache: Mut<LocationTreeCache>| {
+                    let locations = world.resource::<Assets<Locations>>().get(&handle).unwrap();
+                    cache.descendants(&handle, locations, &id("town")).clone()
+                })
+        };
+        assert_eq!(sorted(&cached(&mut app)), ["cellar", "inn", "well"]);
+
+        // Replace the asset like a hot reload does, with the same revision
+        // but the cellar moved out of town
+        let moved = "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town], info: null}
+- {id: cellar, name: Cellar, parent_locations: [], info: null}
+- {id: well, name: Well, parent_locations: [town], info: null}
+";
+        app.world
+            .resource_mut::<Assets<Locations>>()
+            .set_untracked(&handle, locations(moved));
+        app.update();
+        app.update();
+        assert_eq!(
+            cached(&mut app),
+            locations(moved).descendant_ids(&id("town"))
+        );
+        assert_eq!(sorted(&cached(&mut app)), ["inn", "well"]);
+    }
+
+    #[test]
+    fn siblings_share_a_parent() {
+        let locations = locations(
+            "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town], info: null}
+- {id: well, name: Well, parent_locations: [town], info: null}
+- {id: gate, name: Gate, parent_locations: [town], info: null}
+- {id: cellar, name: Cellar, parent_locations: [inn], info: null}
+",
+        );
+        let inn = id("inn");
+        let mut siblings: Vec<_> = locations
+            .iter_siblings(&inn)
+            .map(|l| l.id.0.as_str())
+            .collect();
+        siblings.sort();
+        assert_eq!(siblings, ["gate", "well"]);
+        let town = id("town");
+        assert_eq!(locations.iter_siblings(&town).count(), 0);
+    }
+
+    #[test]
+    fn missing_parents_fail_the_load() {
+        let error = Locations::from_reader(
+            "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town, castle], info: null}
+"
+            .as_bytes(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ClueError::Location(LocationError::MissingParent { location, parent })
+                if location == id("inn") && parent == id("castle")
+        ));
+    }
+
+    #[test]
+    fn every_missing_parent_is_reported() {
+        let error = Locations::from_reader(
+            "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town, castle], info: null}
+- {id: cellar, name: Cellar, parent_locations: [tavern], info: null}
+"
+            .as_bytes(),
+        )
+        .unwrap_err();
+        let errors = match error {
+            ClueError::Location(LocationError::Multiple(errors)) => errors,
+            e => panic!("expected several errors, got {}", e),
+        };
+        let mut missing: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                LocationError::MissingParent { location, parent } => {
+                    (location.0.as_str(), parent.0.as_str())
+                }
+                e => panic!("expected a missing parent, got {}", e),
+            })
+            .collect();
+        missing.sort();
+        assert_eq!(missing, [("cellar", "tavern"), ("inn", "castle")]);
+    }
+
+    const ROADS: &str = "locations:
+- {id: a, name: A, parent_locations: [], adjacent_locations: [b, c], travel_times: {b: 10.0}, info: null}
+- {id: b, name: B, parent_locations: [], info: null}
+- {id: c, name: C, parent_locations: [], adjacent_locations: [b], travel_times: {b: 2.0}, info: null}
+";
+
+    fn adjacent(locations: &Locations, location: &str) -> Vec<String> {
+        let mut ids: Vec<_> = locations
+            .iter_adjacent(&id(location))
+            .map(|l| l.id.0.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn adjacency_is_symmetric_after_load() {
+        let locations = locations(ROADS);
+        assert_eq!(adjacent(&locations, "a"), ["b", "c"]);
+        assert_eq!(adjacent(&locations, "b"), ["a", "c"]);
+        assert_eq!(adjacent(&locations, "c"), ["a", "b"]);
+    }
+
+    #[test]
+    fn cheaper_indirect_route_beats_the_direct_one() {
+        let locations = locations(ROADS);
+        let (route, cost) = locations.travel_route(&id("a"), &id("b")).unwrap();
+        assert_eq!(route, [id("a"), id("c"), id("b")]);
+        assert_eq!(cost, DEFAULT_TRAVEL_TIME + 2.0);
+        assert_eq!(
+            locations.get(&id("a")).unwrap().travel_time_to(&id("b")),
+            10.0
+        );
+        assert!(locations.travel_route(&id("a"), &id("nowhere")).is_none());
+    }
+
+    #[test]
+    fn graphml_has_a_node_per_location_and_an_edge_per_child() {
+        let graphml = locations(
+            "locations:
+- {id: town, name: Town & Docks, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town], info: null}
+",
+        )
+        .to_graphml();
+        assert!(graphml.contains(
+            "    <node id=\"town\">\n      <data key=\"label\">Town &amp; Docks</data>\n    </node>\n"
+        ));
+        assert!(graphml.contains("<node id=\"inn\">"));
+        assert!(graphml.contains("    <edge source=\"town\" target=\"inn\"/>\n"));
+        assert_eq!(graphml.matches("<edge ").count(), 1);
+    }
+
+    const MAPS: &str = "locations:
+- {id: world, name: World, parent_locations: [], info: null}
+- {id: town, name: Town, parent_locations: [world], map: overworld, info: null}
+- {id: inn, name: Inn, parent_locations: [town], map: town_map, info: null}
+- {id: bar, name: Bar, parent_locations: [inn], map: town_map, info: null}
+";
+
+    #[test]
+    fn loading_for_a_map_keeps_its_own_and_mapless_locations() {
+        let map = serde_yaml::from_str("town_map").unwrap();
+        let town_map = Locations::from_yaml_for_map(MAPS.as_bytes(), &map).unwrap();
+        let mut ids: Vec<_> = town_map.iter().map(|l| l.id.0.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["bar", "inn", "world"]);
+        assert_eq!(town_map.iter_parents(&id("inn")).count(), 0);
+        assert_eq!(town_map.iter_children(&id("inn")).count(), 1);
+
+        let mut retained = locations(MAPS);
+        retained.retain_map(&map);
+        assert_eq!(retained.iter().count(), 3);
+    }
+
+    #[test]
+    fn the_plugin_can_load_only_the_active_map() {
+        let folder = std::env::temp_dir().join(format!("revealer-{}-maps", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("world.locations.yml"), MAPS).unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(AssetServerSettings {
+            asset_folder: folder.to_string_lossy().into_owned(),
+            watch_for_changes: false,
+        })
+        .insert_resource(ActiveMap(Some(serde_yaml::from_str("town_map").unwrap())))
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(LocationsAssetPlugin::default().only_active_map());
+        let handle: Handle<Locations> = app
+            .world
+            .resource::<AssetServer>()
+            .load("world.locations.yml");
+        let loaded_until = |app: &mut App, expected: &[&str]| {
+            for _ in 0..500 {
+                app.update();
+                if let Some(locations) = app.world.resource::<Assets<Locations>>().get(&handle) {
+                    let mut ids: Vec<_> = locations.iter().map(|l| l.id.0.as_str()).collect();
+                    ids.sort();
+                    if ids == expected {
+                        return;
+                    }
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            panic!("never loaded {:?}", expected);
+        };
+        loaded_until(&mut app, &["bar", "inn", "world"]);
+
+        app.world.resource_mut::<ActiveMap>().0 = Some(serde_yaml::from_str("overworld").unwrap());
+        loaded_until(&mut app, &["town", "world"]);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn ancestors_are_direct_or_not_but_never_the_location_itself() {
+        let locations = locations(TOWN);
+        assert!(locations.is_ancestor_of(&id("town"), &id("cellar")));
+        assert!(locations.is_ancestor_of(&id("inn"), &id("cellar")));
+        assert!(!locations.is_ancestor_of(&id("well"), &id("cellar")));
+        assert!(!locations.is_ancestor_of(&id("inn"), &id("inn")));
+    }
+
+    #[test]
+    fn merged_locations_relink_children_and_clues() {
+        let mut locations = locations(
+            "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town], info: null}
+- {id: tavern, name: Tavern, parent_locations: [town], info: The old tavern}
+- {id: cellar, name: Cellar, parent_locations: [tavern], info: null}
+",
+        );
+        let mut clues = Clues::from_reader(
+            "clues:\n- {id: mug, information: x, persons: [], locations: [tavern]}\n- {id: key, information: x, persons: [], locations: [inn, tavern]}\n".as_bytes(),
+        )
+        .unwrap();
+        merge_locations(&mut locations, &mut clues, &id("inn"), &id("tavern"));
+
+        assert!(locations.get(&id("tavern")).is_none());
+        let inn = locations.get(&id("inn")).unwrap();
+        assert_eq!(inn.children_locations, [id("cellar")]);
+        assert_eq!(inn.info.as_deref(), Some("The old tavern"));
+        assert_eq!(
+            locations.get(&id("cellar")).unwrap().parent_locations,
+            [id("inn")]
+        );
+        assert_eq!(
+            locations.get(&id("town")).unwrap().children_locations,
+            [id("inn")]
+        );
+        assert_eq!(clues.get_by_location(&id("inn")).count(), 2);
+        assert_eq!(clues.get_by_location(&id("tavern")).count(), 0);
+        assert_eq!(clues.get_str("key").unwrap().locations, [id("inn")]);
+    }
+
+    #[test]
+    fn locations_without_clues_are_dead_zones() {
+        let locations = locations(TOWN);
+        let clues = Clues::from_reader(
+            "clues:\n- {id: mug, information: x, persons: [], locations: [inn]}\n- {id: rope, information: x, persons: [], locations: [well, cellar]}\n".as_bytes(),
+        )
+        .unwrap();
+        let empty: Vec<_> = locations
+            .without_clues(&clues)
+            .map(|l| l.id.0.as_str())
+            .collect();
+        assert_eq!(empty, ["town"]);
+    }
+
+    #[test]
+    fn links_edited_on_one_side_are_inconsistent() {
+        let mut locations = locations(TOWN);
+        assert!(locations.check_consistency().is_empty());
+        locations.get_mut(&id("cellar")).unwrap().parent_locations = vec![id("well")];
+        assert_eq!(locations.check_consistency(), [id("cellar"), id("inn")]);
+    }
+
+    #[test]
+    fn locations_can_be_filtered_by_tag() {
+        let locations = locations(
+            "locations:
+- {id: town, name: Town, parent_locations: [], tags: [urban], info: null}
+- {id: sewer, name: Sewer, parent_locations: [town], tags: [urban, dungeon], info: null}
+- {id: cave, name: Cave, parent_locations: [], tags: [dungeon], info: null}
+",
+        );
+        let tagged = |tag| {
+            let mut ids: Vec<_> = locations.iter_by_tag(tag).map(|l| l.id.0.clone()).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(tagged("dungeon"), ["cave", "sewer"]);
+        assert_eq!(tagged("urban"), ["sewer", "town"]);
+        assert!(tagged("forest").is_empty());
+    }
+
+    #[test]
+    fn bfs_visits_level_by_level() {
+        let locations = locations(TOWN);
+        let mut visits = Vec::new();
+        locations.bfs_from(&id("town"), |l, depth| visits.push((l.id.0.clone(), depth)));
+        assert_eq!(visits[0], ("town".to_string(), 0));
+        let mut middle = visits[1..3].to_vec();
+        middle.sort();
+        assert_eq!(middle, [("inn".to_string(), 1), ("well".to_string(), 1)]);
+        assert_eq!(visits[3], ("cellar".to_string(), 2));
+        assert_eq!(visits.len(), 4);
+    }
+
+    #[test]
+    fn retaining_a_subtree_leaves_no_dangling_links() {
+        let mut locations = locations(TOWN);
+        let subtree = locations.descendant_ids(&id("inn"));
+        locations.retain(|l| l.id == id("inn") || subtree.contains(&l.id));
+        let mut ids: Vec<_> = locations.iter().map(|l| l.id.0.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["cellar", "inn"]);
+        let inn = locations.get(&id("inn")).unwrap();
+        assert!(inn.parent_locations.is_empty());
+        assert_eq!(inn.children_locations, [id("cellar")]);
+        assert!(locations.orphans().next().is_none());
+    }
+
+    #[test]
+    fn locations_can_be_in_several_regions() {
+        let locations = locations(
+            "locations:
+- {id: docks, name: Docks, parent_locations: [], regions: [cult territory, smuggler routes], info: null}
+- {id: temple, name: Temple, parent_locations: [], regions: [cult territory], info: null}
+",
+        );
+        let region = |name| {
+            let mut ids: Vec<_> = locations
+                .iter_in_region(name)
+                .map(|l| l.id.0.clone())
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(region("cult territory"), ["docks", "temple"]);
+        assert_eq!(region("smuggler routes"), ["docks"]);
+        assert!(region("the moon").is_empty());
+    }
+
+    #[test]
+    fn info_variables_are_substituted() {
+        let locations = locations(
+            "locations:
+- {id: castle, name: Castle, parent_locations: [], info: 'Home of {{ ruler }}, guarded by {{guard}}'}
+",
+        );
+        let vars: HashMap<_, _> = [("ruler".to_string(), "Queen Ada".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            locations
+                .get(&id("castle"))
+                .unwrap()
+                .rendered_info(&vars)
+                .as_deref(),
+            Some("Home of Queen Ada, guarded by {{guard}}")
+        );
+    }
+
+    #[test]
+    fn points_are_checked_against_the_area() {
+        let locations = locations(
+            "locations:
+- {id: field, name: Field, parent_locations: [], area: [[0, 0], [4, 0], [4, 4], [0, 4]], info: null}
+- {id: well, name: Well, parent_locations: [], info: null}
+",
+        );
+        let field = locations.get(&id("field")).unwrap();
+        assert!(field.contains_point(Vec2::new(2.0, 2.0)));
+        assert!(!field.contains_point(Vec2::new(5.0, 2.0)));
+        assert!(!locations
+            .get(&id("well"))
+            .unwrap()
+            .contains_point(Vec2::ZERO));
+    }
+
+    #[test]
+    fn tree_cache_is_per_asset_and_revision() {
+        let mut town = locations(TOWN);
+        let other = locations(
+            "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+",
+        );
+        let town_handle = Handle::weak(HandleId::random::<Locations>());
+        let other_handle = Handle::weak(HandleId::random::<Locations>());
+        let mut cache = LocationTreeCache::default();
+        assert_eq!(cache.descendants(&town_handle, &town, &id("town")).len(), 3);
+        assert!(cache
+            .descendants(&other_handle, &other, &id("town"))
+            .is_empty());
+
+        town.retain(|l| l.id != id("inn"));
+        assert_eq!(
+            sorted(cache.descendants(&town_handle, &town, &id("town"))),
+            ["well"]
+        );
     }
 }
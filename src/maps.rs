@@ -0,0 +1,195 @@
+//! Maps, the Asset loader for them and switching between them
+use bevy::{
+    prelude::{warn, Assets, EventReader, Res, ResMut},
+    reflect::{FromReflect, Reflect, TypeUuid},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+pub use assets::MapsAssetPlugin;
+
+use crate::yaml::{self, list_or_null};
+
+/// A wrapper around a string to represent a map
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize, Reflect, FromReflect)]
+#[serde(transparent)]
+pub struct MapId(String);
+
+/// A map, one of the several that can be displayed
+#[derive(Debug, Deserialize)]
+pub struct Map {
+    /// The id of the map
+    pub id: MapId,
+    /// The name of the map
+    pub name: String,
+    /// The path to the image of the map
+    pub image: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MapsFile {
+    #[serde(default, deserialize_with = "list_or_null")]
+    maps: Vec<Map>,
+}
+
+/// A holder for many maps
+#[derive(Debug, Default, TypeUuid)]
+#[uuid = "0c5b3d5e-5d0f-4b8e-9a53-2f4f4d2a6c71"]
+pub struct Maps {
+    maps: HashMap<MapId, Map>,
+}
+
+impl From<MapsFile> for Maps {
+    fn from(file: MapsFile) -> Self {
+        let mut maps = Self::new();
+        for map in file.maps {
+            maps.maps.insert(map.id.clone(), map);
+        }
+        maps
+    }
+}
+
+impl Maps {
+    /// Create a new instance
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Get a [`Option`]al reference to a [`Map`]
+    pub fn get(&self, id: &MapId) -> Option<&Map> {
+        self.maps.get(id)
+    }
+
+    /// Get an [`Iterator`] over all the [`Map`]s
+    pub fn iter(&self) -> impl Iterator<Item = &Map> {
+        self.maps.values()
+    }
+}
+
+/// The map that is currently being displayed
+#[derive(Debug, Default)]
+pub struct ActiveMap(pub Option<MapId>);
+
+/// Event to change the [`ActiveMap`]
+#[derive(Debug)]
+pub struct SwitchMap {
+    /// The map to switch to
+    pub to: MapId,
+}
+
+/// Update the [`ActiveMap`] from [`SwitchMap`] events, ignoring
+/// any maps that haven't been loaded.
+fn switch_map(
+    mut events: EventReader<SwitchMap>,
+    maps: Res<Assets<Maps>>,
+    mut active: ResMut<ActiveMap>,
+) {
+    for SwitchMap { to } in events.iter() {
+        if maps.iter().any(|(_, maps)| maps.get(to).is_some()) {
+            active.0 = Some(to.clone());
+        } else {
+            warn!("Tried to switch to unknown map {:?}", to);
+        }
+    }
+}
+
+mod assets {
+    use bevy::{
+        asset::{AssetLoader, LoadedAsset},
+        prelude::{AddAsset, Plugin},
+    };
+
+    use super::{switch_map, yaml, ActiveMap, Maps, MapsFile, SwitchMap};
+    use crate::extensions::AssetExtensions;
+
+    /// Bevy plugin to load a maps file and switch between them
+    pub struct MapsAssetPlugin {
+        extensions: AssetExtensions,
+    }
+
+    impl Default for MapsAssetPlugin {
+        fn default() -> Self {
+            Self {
+                extensions: AssetExtensions::new("maps.yml"),
+            }
+        }
+    }
+
+    impl MapsAssetPlugin {
+        /// Load maps from files with any of the given extensions instead
+        /// of the default `maps.yml`. Extensions are given without a
+        /// leading `.`, e.g. `maps.yaml`.
+        /// An empty list keeps the default.
+        pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+            self.extensions.set(extensions);
+            self
+        }
+    }
+
+    impl Plugin for MapsAssetPlugin {
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_asset::<Maps>()
+                .add_asset_loader(MapsAssetLoader {
+                    extensions: self.extensions.get(),
+                })
+                .init_resource::<ActiveMap>()
+                .add_event::<SwitchMap>()
+                .add_system(switch_map);
+        }
+    }
+
+    struct MapsAssetLoader {
+        extensions: Vec<&'static str>,
+    }
+    impl AssetLoader for MapsAssetLoader {
+        fn load<'a>(
+            &'a self,
+            bytes: &'a [u8],
+            load_context: &'a mut bevy::asset::LoadContext,
+        ) -> bevy::utils::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+            Box::pin(async move {
+                let maps_file: MapsFile = yaml::from_slice(bytes)?;
+                let maps: Maps = maps_file.into();
+                load_context.set_default_asset(LoadedAsset::new(maps));
+                Ok(())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &self.extensions
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::AssetPlugin,
+        prelude::{App, MinimalPlugins},
+    };
+
+    use super::*;
+
+    fn map(id: &str) -> MapId {
+        MapId(id.to_string())
+    }
+
+    #[test]
+    fn switching_ignores_unknown_maps() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_plugin(MapsAssetPlugin::default());
+        let file: MapsFile =
+            serde_yaml::from_str("maps:\n- {id: town, name: Town, image: town.png}\n").unwrap();
+        let _maps = app.world.resource_mut::<Assets<Maps>>().add(file.into());
+
+        app.world.send_event(SwitchMap { to: map("town") });
+        app.update();
+        assert_eq!(app.world.resource::<ActiveMap>().0, Some(map("town")));
+
+        app.world.send_event(SwitchMap { to: map("sewers") });
+        app.update();
+        assert_eq!(app.world.resource::<ActiveMap>().0, Some(map("town")));
+    }
+}
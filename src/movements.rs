@@ -0,0 +1,224 @@
+//! Where persons are over time, and the Asset loader for them
+use bevy::{reflect::TypeUuid, utils::HashMap};
+use serde::Deserialize;
+
+pub use assets::MovementsAssetPlugin;
+
+use crate::{
+    clues::{ClueId, Clues, PersonId},
+    locations::LocationId,
+    yaml::{self, list_or_null},
+};
+
+/// A person being at a location for a span of in-game time
+#[derive(Debug, Clone, Deserialize)]
+pub struct Movement {
+    /// The person that moved
+    pub person: PersonId,
+    /// Where they were
+    pub location: LocationId,
+    /// The in-game time they arrived
+    pub from: i64,
+    /// The in-game time they left, if they did
+    #[serde(default)]
+    pub until: Option<i64>,
+}
+
+impl Movement {
+    /// Check whether the person was at the location at an in-game time,
+    /// both ends are inclusive
+    pub fn covers(&self, time: i64) -> bool {
+        self.from <= time && self.until.is_none_or(|until| time <= until)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MovementsFile {
+    #[serde(default, deserialize_with = "list_or_null")]
+    movements: Vec<Movement>,
+}
+
+/// The movements of many persons
+#[derive(Debug, Default, TypeUuid)]
+#[uuid = "5f0c2a8e-3b7d-4e61-9c4f-8d2e7a1b6c90"]
+pub struct Movements {
+    by_person: HashMap<PersonId, Vec<Movement>>,
+}
+
+impl From<MovementsFile> for Movements {
+    fn from(file: MovementsFile) -> Self {
+        let mut movements = Self::new();
+        for movement in file.movements {
+            movements.insert(movement);
+        }
+        movements
+    }
+}
+
+impl Movements {
+    /// Create a new instance
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a movement
+    pub fn insert(&mut self, movement: Movement) {
+        self.by_person
+            .entry(movement.person.clone())
+            .or_default()
+            .push(movement);
+    }
+
+    /// Get an [`Iterator`] over the movements of a person
+    pub fn of_person(&self, person: &PersonId) -> impl Iterator<Item = &Movement> {
+        self.by_person.get(person).into_iter().flatten()
+    }
+
+    /// Get where a person was at an in-game time. If several movements
+    /// cover the time the one that started latest wins.
+    pub fn location_of(&self, person: &PersonId, time: i64) -> Option<&LocationId> {
+        self.of_person(person)
+            .filter(|m| m.covers(time))
+            .max_by_key(|m| m.from)
+            .map(|m| &m.location)
+    }
+}
+
+/// Find the clues that place a person somewhere the [`Movements`] say
+/// they weren't.
+///
+/// A clue with a [`Clue::time`](crate::clues::Clue::time) implies each of
+/// its persons was at one of its locations at that time. It is flagged if,
+/// for any of its persons, the movements put them at a location that isn't
+/// one of the clue's. Clues without a time or locations, and persons with
+/// no movement covering the time, can't contradict anything. The ids are
+/// sorted.
+pub fn check_movement_consistency(clues: &Clues, movements: &Movements) -> Vec<ClueId> {
+    let mut inconsistent: Vec<_> = clues
+        .iter()
+        .filter(|c| !c.locations.is_empty())
+        .filter(|c| {
+            c.time.is_some_and(|time| {
+                c.persons.iter().any(|p| {
+                    movements
+                        .location_of(p, time)
+                        .is_some_and(|l| !c.locations.contains(l))
+                })
+            })
+        })
+        .map(|c| c.id.clone())
+        .collect();
+    inconsistent.sort();
+    inconsistent
+}
+
+mod assets {
+    use bevy::{
+        asset::{AssetLoader, LoadedAsset},
+        prelude::{AddAsset, Plugin},
+    };
+
+    use super::{yaml, Movements, MovementsFile};
+    use crate::extensions::AssetExtensions;
+
+    /// Bevy plugin to load a movements file
+    pub struct MovementsAssetPlugin {
+        extensions: AssetExtensions,
+    }
+
+    impl Default for MovementsAssetPlugin {
+        fn default() -> Self {
+            Self {
+                extensions: AssetExtensions::new("movements.yml"),
+            }
+        }
+    }
+
+    impl MovementsAssetPlugin {
+        /// Load movements from files with any of the given extensions instead
+        /// of the default `movements.yml`. Extensions are given without a
+        /// leading `.`, e.g. `movements.yaml`.
+        /// An empty list keeps the default.
+        pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+            self.extensions.set(extensions);
+            self
+        }
+    }
+
+    impl Plugin for MovementsAssetPlugin {
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_asset::<Movements>()
+                .add_asset_loader(MovementsAssetLoader {
+                    extensions: self.extensions.get(),
+                });
+        }
+    }
+
+    struct MovementsAssetLoader {
+        extensions: Vec<&'static str>,
+    }
+    impl AssetLoader for MovementsAssetLoader {
+        fn load<'a>(
+            &'a self,
+            bytes: &'a [u8],
+            load_context: &'a mut bevy::asset::LoadContext,
+        ) -> bevy::utils::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+            Box::pin(async move {
+                let movements_file: MovementsFile = yaml::from_slice(bytes)?;
+                let movements: Movements = movements_file.into();
+                load_context.set_default_asset(LoadedAsset::new(movements));
+                Ok(())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &self.extensions
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movements() -> Movements {
+        yaml::from_slice::<MovementsFile>(
+            b"movements:
+- {person: bob, location: docks, from: 0}
+- {person: bob, location: inn, from: 10, until: 20}
+",
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn latest_movement_covering_the_time_wins() {
+        let movements = movements();
+        let bob = serde_yaml::from_str("bob").unwrap();
+        let at = |time| movements.location_of(&bob, time).cloned();
+        assert_eq!(at(5), Some(serde_yaml::from_str("docks").unwrap()));
+        assert_eq!(at(20), Some(serde_yaml::from_str("inn").unwrap()));
+        assert_eq!(at(21), Some(serde_yaml::from_str("docks").unwrap()));
+        assert_eq!(at(-1), None);
+    }
+
+    #[test]
+    fn clues_placing_a_person_elsewhere_are_inconsistent() {
+        let clues = Clues::from_reader(
+            "clues:
+- {id: seen_at_inn, information: x, persons: [bob], locations: [inn], time: 15}
+- {id: seen_at_docks, information: x, persons: [bob], locations: [docks], time: 15}
+- {id: no_time, information: x, persons: [bob], locations: [docks]}
+- {id: before_anything, information: x, persons: [bob], locations: [inn], time: -5}
+"
+            .as_bytes(),
+        )
+        .unwrap();
+        let inconsistent = check_movement_consistency(&clues, &movements());
+        assert_eq!(
+            inconsistent,
+            [serde_yaml::from_str::<ClueId>("seen_at_docks").unwrap()]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Persons and the Asset loader for them
+use bevy::{reflect::TypeUuid, utils::HashMap};
+use serde::Deserialize;
+
+pub use assets::PersonsAssetPlugin;
+
+use crate::{
+    clues::{Clue, ClueError, Clues, PersonId},
+    collation::{compare_names, names_match},
+    yaml::{self, list_or_null},
+};
+
+/// A person
+#[derive(Debug, Deserialize)]
+pub struct Person {
+    /// The id of the person
+    pub id: PersonId,
+    /// The name of the person
+    pub name: String,
+    /// A description of the person (intended for public knowledge)
+    pub info: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PersonsFile {
+    #[serde(default, deserialize_with = "list_or_null")]
+    persons: Vec<Person>,
+}
+
+/// A holder for many persons
+#[derive(Debug, Default, TypeUuid)]
+#[uuid = "b8a0f3e4-4c55-4f0e-8d2a-6e0f6f62c1d3"]
+pub struct Persons {
+    persons: HashMap<PersonId, Person>,
+}
+
+impl From<PersonsFile> for Persons {
+    fn from(file: PersonsFile) -> Self {
+        let mut persons = Self::new();
+        for person in file.persons {
+            persons.persons.insert(person.id.clone(), person);
+        }
+        persons
+    }
+}
+
+/// Load several persons files into one [`Persons`]. Every problem found is
+/// returned along with whatever persons could be loaded.
+pub(crate) fn load_files(files: &[Vec<u8>]) -> (Persons, Vec<ClueError>) {
+    let mut persons = Persons::new();
+    let mut errors = Vec::new();
+    for bytes in files {
+        match yaml::from_slice::<PersonsFile>(bytes) {
+            Ok(file) => persons.persons.extend(Persons::from(file).persons),
+            Err(e) => errors.push(e.into()),
+        }
+    }
+    (persons, errors)
+}
+
+impl Persons {
+    /// Create a new instance
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Get a [`Option`]al reference to a [`Person`]
+    pub fn get(&self, id: &PersonId) -> Option<&Person> {
+        self.persons.get(id)
+    }
+
+    /// Get a [`Option`]al mutable reference to a [`Person`]
+    pub fn get_mut(&mut self, id: &PersonId) -> Option<&mut Person> {
+        self.persons.get_mut(id)
+    }
+
+    /// Get an [`Iterator`] over all the [`Person`]s
+    pub fn iter(&self) -> impl Iterator<Item = &Person> {
+        self.persons.values()
+    }
+
+    /// Find a [`Person`] by their name, ignoring accents and case like
+    /// [`Persons::iter_sorted_by_name`]. If several persons share the name
+    /// the one with the lowest [`PersonId`] is returned.
+    pub fn find_by_name(&self, name: &str) -> Option<&Person> {
+        self.persons
+            .values()
+            .filter(|p| names_match(&p.name, name))
+            .min_by(|a, b| a.id.cmp(&b.id))
+    }
+
+    /// Get an [`Iterator`] over all the [`Person`]s sorted by their name.
+    ///
+    /// Names are compared with their accents removed and case folded,
+    /// so `"Émile"` sorts alongside `"emile"` rather than after `"Zoé"`.
+    pub fn iter_sorted_by_name(&self) -> impl Iterator<Item = &Person> {
+        let mut persons: Vec<_> = self.persons.values().collect();
+        persons.sort_by(|a, b| compare_names(&a.name, &b.name));
+        persons.into_iter()
+    }
+}
+
+/// Get the clues linked to a person by their name rather than their id,
+/// see [`Persons::find_by_name`]. Nothing is returned for unknown names.
+pub fn clues_for_person_name<'a>(
+    clues: &'a Clues,
+    persons: &Persons,
+    name: &str,
+) -> impl Iterator<Item = &'a Clue> {
+    persons
+        .find_by_name(name)
+        .map(|p| clues.get_by_person(&p.id))
+        .into_iter()
+        .flatten()
+}
+
+/// Get the persons that no clue references, e.g. NPCs that were defined
+/// but never used
+pub fn unreferenced_persons<'a>(
+    persons: &'a Persons,
+    clues: &'a Clues,
+) -> impl Iterator<Item = &'a Person> {
+    persons
+        .iter()
+        .filter(|p| clues.get_by_person(&p.id).next().is_none())
+}
+
+mod assets {
+    use bevy::{
+        asset::{AssetLoader, LoadedAsset},
+        prelude::{AddAsset, Plugin},
+    };
+
+    use super::{yaml, Persons, PersonsFile};
+    use crate::extensions::AssetExtensions;
+
+    /// Bevy plugin to load a persons file
+    pub struct PersonsAssetPlugin {
+        extensions: AssetExtensions,
+    }
+
+    impl Default for PersonsAssetPlugin {
+        fn default() -> Self {
+            Self {
+                extensions: AssetExtensions::new("persons.yml"),
+            }
+        }
+    }
+
+    impl PersonsAssetPlugin {
+        /// Load persons from files with any of the given extensions instead
+        /// of the default `persons.yml`. Extensions are given without a
+        /// leading `.`, e.g. `persons.yaml`.
+        /// An empty list keeps the default.
+        pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+            self.extensions.set(extensions);
+            self
+        }
+    }
+
+    impl Plugin for PersonsAssetPlugin {
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_asset::<Persons>()
+                .add_asset_loader(PersonsAssetLoader {
+                    extensions: self.extensions.get(),
+                });
+        }
+    }
+
+    struct PersonsAssetLoader {
+        extensions: Vec<&'static str>,
+    }
+    impl AssetLoader for PersonsAssetLoader {
+        fn load<'a>(
+            &'a self,
+            bytes: &'a [u8],
+            load_context: &'a mut bevy::asset::LoadContext,
+        ) -> bevy::utils::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+            Box::pin(async move {
+                let persons_file: PersonsFile = yaml::from_slice(bytes)?;
+                let persons: Persons = persons_file.into();
+                load_context.set_default_asset(LoadedAsset::new(persons));
+                Ok(())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &self.extensions
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use super::*;
+
+    #[test]
+    fn accented_names_sort_with_their_letter() {
+        let persons: Persons = serde_yaml::from_str::<PersonsFile>(
+            "persons:
+- {id: zoe, name: Zoé, info: null}
+- {id: fabien, name: Fabien, info: null}
+- {id: emile, name: Émile, info: null}
+",
+        )
+        .unwrap()
+        .into();
+        let names: Vec<_> = persons
+            .iter_sorted_by_name()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["Émile", "Fabien", "Zoé"]);
+    }
+
+    fn persons() -> Persons {
+        let (persons, errors) = load_files(&[b"persons:
+- {id: emile, name: \xc3\x89mile, info: null}
+- {id: zoe, name: Zo\xc3\xa9, info: null}
+"
+        .to_vec()]);
+        assert!(errors.is_empty());
+        persons
+    }
+
+    #[test]
+    fn persons_in_no_clue_are_unreferenced() {
+        let persons = persons();
+        let clues = Clues::from_reader(
+            "clues:\n- {id: letter, information: x, persons: [emile], locations: []}\n".as_bytes(),
+        )
+        .unwrap();
+        let unreferenced: Vec<_> = unreferenced_persons(&persons, &clues)
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(unreferenced, ["Zoé"]);
+    }
+
+    #[test]
+    fn find_by_name_ignores_accents_and_case() {
+        let persons = persons();
+        assert_eq!(
+            persons.find_by_name("emile").unwrap().id.borrow() as &str,
+            "emile"
+        );
+        assert_eq!(
+            persons.find_by_name("ZOE").unwrap().id.borrow() as &str,
+            "zoe"
+        );
+        assert!(persons.find_by_name("Emilie").is_none());
+    }
+
+    #[test]
+    fn clues_for_person_name_finds_accented_person() {
+        let persons = persons();
+        let clues = Clues::from_reader(
+            "clues:\n- {id: letter, information: x, persons: [emile], locations: []}\n".as_bytes(),
+        )
+        .unwrap();
+        let found: Vec<_> = clues_for_person_name(&clues, &persons, "emile")
+            .map(|c| -> &str { c.id.borrow() })
+            .collect();
+        assert_eq!(found, ["letter"]);
+        assert_eq!(clues_for_person_name(&clues, &persons, "nobody").count(), 0);
+    }
+}
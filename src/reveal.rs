@@ -0,0 +1,294 @@
+//! Revealing clues gradually instead of all at once
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::{
+    App, Assets, EventReader, EventWriter, Handle, Local, ParallelSystemDescriptorCoercion, Plugin,
+    Res, ResMut, Time,
+};
+
+use crate::clues::{ClueId, Clues};
+
+/// Event to add a clue to the end of the [`PendingReveals`] queue
+#[derive(Debug, Clone)]
+pub struct QueueReveal {
+    /// The clue to reveal
+    pub id: ClueId,
+}
+
+/// Event to reveal several clues straight away, skipping the
+/// [`PendingReveals`] queue, e.g. when importing from another tool
+#[derive(Debug, Clone)]
+pub struct RevealBulk {
+    /// The clues to reveal
+    pub ids: Vec<ClueId>,
+}
+
+/// Event sent whenever a clue is revealed by the [`RevealPlugin`]. Ids that
+/// aren't clues, or clues that were already known, aren't sent.
+#[derive(Debug, Clone)]
+pub struct ClueRevealed {
+    /// The clue that was revealed
+    pub id: ClueId,
+}
+
+/// The clues waiting to be revealed, in order
+#[derive(Debug, Default)]
+pub struct PendingReveals(pub VecDeque<ClueId>);
+
+/// How long to wait between revealing each of the [`PendingReveals`]
+#[derive(Debug, Clone, Copy)]
+pub struct RevealRate {
+    /// The time between reveals
+    pub interval: Duration,
+}
+
+impl Default for RevealRate {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Every clue revealed by the [`RevealPlugin`], in the order they were
+/// revealed, along with the time in seconds since startup they were
+/// revealed at. Clues that were already known aren't logged again.
+#[derive(Debug, Default)]
+pub struct RevealLog {
+    reveals: Vec<(ClueId, i64)>,
+}
+
+impl RevealLog {
+    /// Get the reveals in order, to replay how the investigation unfolded
+    pub fn replay(&self) -> &[(ClueId, i64)] {
+        &self.reveals
+    }
+}
+
+/// Bevy plugin to reveal clues queued with [`QueueReveal`] one at a time,
+/// at the pace set by the [`RevealRate`] resource
+pub struct RevealPlugin;
+impl Plugin for RevealPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingReveals>()
+            .init_resource::<RevealRate>()
+            .init_resource::<RevealLog>()
+            .add_event::<QueueReveal>()
+            .add_event::<ClueRevealed>()
+            .add_event::<RevealBulk>()
+            .add_system(queue_reveals.before(reveal_pending))
+            .add_system(reveal_bulk)
+            .add_system(reveal_pending);
+    }
+}
+
+fn queue_reveals(mut events: EventReader<QueueReveal>, mut pending: ResMut<PendingReveals>) {
+    for QueueReveal { id } in events.iter() {
+        pending.0.push_back(id.clone());
+    }
+}
+
+fn reveal_pending(
+    time: Res<Time>,
+    rate: Res<RevealRate>,
+    mut elapsed: Local<Duration>,
+    mut pending: ResMut<PendingReveals>,
+    mut assets: ResMut<Assets<Clues>>,
+    mut log: ResMut<RevealLog>,
+    mut revealed: EventWriter<ClueRevealed>,
+) {
+    *elapsed += time.delta();
+    while *elapsed >= rate.interval {
+        let id = match pending.0.pop_front() {
+            Some(id) => id,
+            None => {
+                // Don't build up time while nothing is queued, so the next
+                // clue still waits a whole interval.
+                *elapsed = Duration::ZERO;
+                return;
+            }
+        };
+        // Ids that aren't clues, or are already known, are dropped without
+        // using up an interval
+        if !reveal(&mut assets, &id) {
+            continue;
+        }
+        *elapsed -= rate.interval;
+        log.reveals
+            .push((id.clone(), time.seconds_since_startup() as i64));
+        revealed.send(ClueRevealed { id });
+
+        if rate.interval.is_zero() {
+            // Avoid looping forever, reveal at most one per frame
+            *elapsed = Duration::ZERO;
+            return;
+        }
+    }
+}
+
+fn reveal_bulk(
+    mut events: EventReader<RevealBulk>,
+    time: Res<Time>,
+    mut assets: ResMut<Assets<Clues>>,
+    mut log: ResMut<RevealLog>,
+    mut revealed: EventWriter<ClueRevealed>,
+) {
+    for RevealBulk { ids } in events.iter() {
+        for id in ids {
+            if reveal(&mut assets, id) {
+                log.reveals
+                    .push((id.clone(), time.seconds_since_startup() as i64));
+                revealed.send(ClueRevealed { id: id.clone() });
+            }
+        }
+    }
+}
+
+/// Mark a clue as known in all the [`Clues`] containing it, returns `true`
+/// if it wasn't already known in any of them
+fn reveal(assets: &mut Assets<Clues>, id: &ClueId) -> bool {
+    let handles: Vec<_> = assets
+        .iter()
+        .filter(|(_, clues)| clues.get(id).is_some_and(|c| !c.known))
+        .map(|(handle, _)| handle)
+        .collect();
+    let mut changed = false;
+    for handle in handles {
+        if let Some(clues) = assets.get_mut(&Handle::weak(handle)) {
+            changed |= clues.set_known(id, true);
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::{AddAsset, AssetPlugin},
+        core::CorePlugin,
+        ecs::event::{Events, ManualEventReader},
+        utils::Instant,
+    };
+
+    use super::*;
+
+    /// An app with the [`RevealPlugin`] whose clock only moves when told to
+    struct TestApp {
+        app: App,
+        now: Instant,
+        reader: ManualEventReader<ClueRevealed>,
+        // Keeps the clues from being unloaded
+        _clues: Handle<Clues>,
+    }
+
+    impl TestApp {
+        fn new() -> Self {
+            let mut app = App::new();
+            app.add_plugin(CorePlugin)
+                .add_plugin(AssetPlugin)
+                .add_asset::<Clues>()
+                .insert_resource(Time::default())
+                .add_plugin(RevealPlugin);
+            let clues = Clues::from_reader(
+                "clues:\n- {id: a, information: x, persons: [], locations: []}\n- {id: b, information: x, persons: [], locations: []}\n- {id: c, information: x, persons: [], locations: []}\n".as_bytes(),
+            )
+            .unwrap();
+            let handle = app.world.resource_mut::<Assets<Clues>>().add(clues);
+            let now = Instant::now();
+            app.world.resource_mut::<Time>().update_with_instant(now);
+            Self {
+                app,
+                now,
+                reader: Default::default(),
+                _clues: handle,
+            }
+        }
+
+        /// Move the clock on and run a frame, returning the clues revealed
+        fn step(&mut self, secs: u64) -> Vec<String> {
+            self.now += Duration::from_secs(secs);
+            let now = self.now;
+            self.app
+                .world
+                .resource_mut::<Time>()
+                .update_with_instant(now);
+            self.app.update();
+            let events = self.app.world.resource::<Events<ClueRevealed>>();
+            self.reader
+                .iter(events)
+                .map(|e| format!("{:?}", e.id))
+                .collect()
+        }
+
+        fn queue(&mut self, id: &str) {
+            self.app.world.send_event(QueueReveal {
+                id: serde_yaml::from_str(id).unwrap(),
+            });
+        }
+    }
+
+    #[test]
+    fn reveals_one_clue_per_interval() {
+        let mut app = TestApp::new();
+        app.queue("a");
+        app.queue("b");
+        assert!(app.step(0).is_empty());
+        assert!(app.step(5).is_empty());
+        assert_eq!(app.step(5), [r#"ClueId("a")"#]);
+        assert!(app.step(9).is_empty());
+        assert_eq!(app.step(1), [r#"ClueId("b")"#]);
+        assert_eq!(app.app.world.resource::<RevealLog>().replay().len(), 2);
+    }
+
+    #[test]
+    fn idle_time_doesnt_count_towards_the_next_reveal() {
+        let mut app = TestApp::new();
+        app.step(0);
+        app.step(30);
+        app.queue("c");
+        assert!(app.step(0).is_empty());
+        assert!(app.step(9).is_empty());
+        assert_eq!(app.step(1), [r#"ClueId("c")"#]);
+    }
+
+    #[test]
+    fn reveal_log_appends_in_order() {
+        let mut app = TestApp::new();
+        app.queue("b");
+        app.queue("a");
+        app.step(0);
+        app.step(10);
+        app.step(10);
+        let log = app.app.world.resource::<RevealLog>().replay();
+        let ids: Vec<_> = log.iter().map(|(id, _)| format!("{:?}", id)).collect();
+        assert_eq!(ids, [r#"ClueId("b")"#, r#"ClueId("a")"#]);
+        assert!(log[0].1 < log[1].1);
+    }
+
+    #[test]
+    fn bulk_reveals_skip_the_queue() {
+        let mut app = TestApp::new();
+        app.app.world.send_event(RevealBulk {
+            ids: vec![
+                serde_yaml::from_str("a").unwrap(),
+                serde_yaml::from_str("b").unwrap(),
+            ],
+        });
+        assert_eq!(app.step(0), [r#"ClueId("a")"#, r#"ClueId("b")"#]);
+    }
+
+    #[test]
+    fn unknown_and_known_clues_send_nothing() {
+        let mut app = TestApp::new();
+        app.queue("missing");
+        app.queue("a");
+        app.queue("a");
+        app.queue("b");
+        app.step(0);
+        // Skipping the missing and the known clue doesn't use up an interval
+        assert_eq!(app.step(10), [r#"ClueId("a")"#]);
+        assert_eq!(app.step(10), [r#"ClueId("b")"#]);
+        assert!(app.step(10).is_empty());
+        assert_eq!(app.app.world.resource::<RevealLog>().replay().len(), 2);
+    }
+}
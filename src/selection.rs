@@ -0,0 +1,110 @@
+//! What is currently selected in the game
+use bevy::{
+    prelude::{App, Assets, EventReader, Plugin, Res, ResMut},
+    utils::HashSet,
+};
+
+use crate::{
+    clues::{Clues, PersonId},
+    locations::LocationId,
+};
+
+/// The person currently selected, whose related locations and movements
+/// are displayed
+#[derive(Debug, Default, Clone)]
+pub struct Selection(pub Option<PersonId>);
+
+/// Event to change the [`Selection`], [`None`] to select nobody
+#[derive(Debug, Clone)]
+pub struct PersonSelected {
+    /// The person selected
+    pub person: Option<PersonId>,
+}
+
+/// The locations linked to the selected person's clues, for the renderer
+/// to highlight. Empty when nobody is selected.
+#[derive(Debug, Default, Clone)]
+pub struct HighlightedLocations(pub HashSet<LocationId>);
+
+/// Bevy plugin to keep the [`Selection`] and [`HighlightedLocations`] up
+/// to date with the [`PersonSelected`] events
+pub struct SelectionPlugin;
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .init_resource::<HighlightedLocations>()
+            .add_event::<PersonSelected>()
+            .add_system(highlight_person_locations);
+    }
+}
+
+/// Highlight the locations of the clues linked to the newly selected
+/// person, across all the loaded [`Clues`]
+pub fn highlight_person_locations(
+    mut events: EventReader<PersonSelected>,
+    assets: Res<Assets<Clues>>,
+    mut selection: ResMut<Selection>,
+    mut highlighted: ResMut<HighlightedLocations>,
+) {
+    // Only the latest selection matters
+    let person = match events.iter().last() {
+        Some(PersonSelected { person }) => person,
+        None => return,
+    };
+    highlighted.0 = match person {
+        Some(person) => assets
+            .iter()
+            .flat_map(|(_, clues)| clues.get_by_person(person))
+            .flat_map(|c| c.locations.iter().cloned())
+            .collect(),
+        None => HashSet::new(),
+    };
+    selection.0 = person.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::{AddAsset, AssetPlugin},
+        prelude::{App, MinimalPlugins},
+    };
+
+    use super::*;
+
+    #[test]
+    fn selecting_a_person_highlights_their_clue_locations() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Clues>()
+            .add_plugin(SelectionPlugin);
+        let clues = Clues::from_reader(
+            "clues:\n- [knife, x, [bob], [kitchen, hall]]\n- [coat, x, [alice], [porch]]\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let _clues = app.world.resource_mut::<Assets<Clues>>().add(clues);
+        let bob: PersonId = serde_yaml::from_str("bob").unwrap();
+
+        app.world.send_event(PersonSelected {
+            person: Some(bob.clone()),
+        });
+        app.update();
+        let mut highlighted: Vec<_> = app
+            .world
+            .resource::<HighlightedLocations>()
+            .0
+            .iter()
+            .cloned()
+            .collect();
+        highlighted.sort();
+        let expected: Vec<LocationId> = serde_yaml::from_str("[hall, kitchen]").unwrap();
+        assert_eq!(highlighted, expected);
+        assert_eq!(app.world.resource::<Selection>().0, Some(bob));
+
+        app.world.send_event(PersonSelected { person: None });
+        app.update();
+        assert!(app.world.resource::<HighlightedLocations>().0.is_empty());
+        assert_eq!(app.world.resource::<Selection>().0, None);
+    }
+}
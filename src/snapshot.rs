@@ -0,0 +1,141 @@
+//! Saving and restoring the state of a session
+use bevy::{
+    prelude::{Assets, Handle, World},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clues::{ClueId, Clues, PersonId},
+    maps::{ActiveMap, MapId},
+    selection::Selection,
+    PlayerKnowledge,
+};
+
+/// Everything needed to resume a session, which clues are known, the
+/// clues known by each player, the [`ActiveMap`] and the current
+/// [`Selection`]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    /// The [`known`](crate::clues::Clue::known) flag of every clue in all
+    /// the loaded [`Clues`], see [`Clues::known_snapshot`]. The assets are
+    /// merged by id, a clue in several of them is known if it's known in
+    /// any, and restoring it sets the flag in all of them.
+    pub known: HashMap<ClueId, bool>,
+    /// The clues known by each player
+    pub knowledge: PlayerKnowledge,
+    /// The map being displayed
+    pub active_map: Option<MapId>,
+    /// The person selected
+    pub selection: Option<PersonId>,
+}
+
+/// Capture a [`GameSnapshot`] from the resources in the [`World`],
+/// any missing resources are treated as their default
+pub fn capture_snapshot(world: &World) -> GameSnapshot {
+    let mut known = HashMap::new();
+    if let Some(assets) = world.get_resource::<Assets<Clues>>() {
+        for (_, clues) in assets.iter() {
+            for (id, is_known) in clues.known_snapshot() {
+                *known.entry(id).or_default() |= is_known;
+            }
+        }
+    }
+    GameSnapshot {
+        known,
+        knowledge: world
+            .get_resource::<PlayerKnowledge>()
+            .cloned()
+            .unwrap_or_default(),
+        active_map: world.get_resource::<ActiveMap>().and_then(|m| m.0.clone()),
+        selection: world.get_resource::<Selection>().and_then(|s| s.0.clone()),
+    }
+}
+
+/// Restore a [`GameSnapshot`], replacing the resources in the [`World`]
+/// and setting the known flags of the loaded [`Clues`]. Clues loaded after
+/// this are left as they are in their files.
+pub fn apply_snapshot(world: &mut World, snapshot: GameSnapshot) {
+    if let Some(mut assets) = world.get_resource_mut::<Assets<Clues>>() {
+        let handles: Vec<_> = assets.iter().map(|(handle, _)| handle).collect();
+        for handle in handles {
+            if let Some(clues) = assets.get_mut(&Handle::weak(handle)) {
+                clues.apply_known_snapshot(&snapshot.known);
+            }
+        }
+    }
+    world.insert_resource(snapshot.knowledge);
+    world.insert_resource(ActiveMap(snapshot.active_map));
+    world.insert_resource(Selection(snapshot.selection));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::{AddAsset, AssetPlugin},
+        prelude::{App, MinimalPlugins},
+    };
+
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trips() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Clues>();
+        let clues =
+            Clues::from_reader("clues:\n- {id: knife, information: x, persons: [], locations: []}\n- {id: cup, information: x, persons: [], locations: []}\n".as_bytes())
+                .unwrap();
+        let handle = app.world.resource_mut::<Assets<Clues>>().add(clues);
+        // The knife is also in another asset, where it isn't known
+        let other = Clues::from_reader(
+            "clues:\n- {id: knife, information: x, persons: [], locations: []}\n".as_bytes(),
+        )
+        .unwrap();
+        let other = app.world.resource_mut::<Assets<Clues>>().add(other);
+        let knife: ClueId = serde_yaml::from_str("knife").unwrap();
+        let alice: PersonId = serde_yaml::from_str("alice").unwrap();
+        let mut knowledge = PlayerKnowledge::default();
+        knowledge.reveal_to(&alice, knife.clone());
+        app.insert_resource(knowledge)
+            .insert_resource(ActiveMap(Some(serde_yaml::from_str("city").unwrap())))
+            .insert_resource(Selection(Some(alice.clone())));
+        app.world
+            .resource_mut::<Assets<Clues>>()
+            .get_mut(&handle)
+            .unwrap()
+            .set_known(&knife, true);
+
+        let snapshot = capture_snapshot(&app.world);
+        assert!(snapshot.known[&knife]);
+        let yaml = serde_yaml::to_string(&snapshot).unwrap();
+
+        // Forget everything, then restore it
+        app.world
+            .resource_mut::<Assets<Clues>>()
+            .get_mut(&handle)
+            .unwrap()
+            .set_known(&knife, false);
+        app.insert_resource(PlayerKnowledge::default())
+            .insert_resource(ActiveMap(None))
+            .insert_resource(Selection(None));
+        apply_snapshot(&mut app.world, serde_yaml::from_str(&yaml).unwrap());
+
+        let assets = app.world.resource::<Assets<Clues>>();
+        let clues = assets.get(&handle).unwrap();
+        assert!(clues.get(&knife).unwrap().known);
+        assert!(!clues.get_str("cup").unwrap().known);
+        assert!(assets.get(&other).unwrap().get(&knife).unwrap().known);
+        assert!(app
+            .world
+            .resource::<PlayerKnowledge>()
+            .known_by(&alice)
+            .contains(&knife));
+        assert_eq!(
+            app.world.resource::<ActiveMap>().0,
+            Some(serde_yaml::from_str("city").unwrap())
+        );
+        assert_eq!(app.world.resource::<Selection>().0, Some(alice));
+    }
+}
@@ -0,0 +1,165 @@
+//! Syncing revealed clues from the DM build to the player builds
+use bevy::prelude::{App, Assets, EventReader, EventWriter, Handle, Plugin, Res, ResMut};
+
+use crate::{
+    clues::{ClueId, Clues},
+    reveal::ClueRevealed,
+    Mode,
+};
+
+/// A clue that has been revealed. Sent as an event in the DM build to
+/// share it with the players, and marked as known in the local [`Clues`]
+/// when it is received by a player build.
+///
+/// Syncing only ever reveals clues, one made unknown again in the DM build
+/// stays known for the players.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClueStatePacket {
+    /// The id of the clue
+    pub id: ClueId,
+}
+
+/// A way of getting [`ClueStatePacket`]s between builds, e.g. over TCP
+/// or a channel
+pub trait ClueTransport: Send + Sync + 'static {
+    /// Send a packet to the other end
+    fn send(&self, packet: &ClueStatePacket);
+    /// Get all the packets that have arrived since the last call
+    fn recv(&self) -> Vec<ClueStatePacket>;
+}
+
+struct Transport<T>(T);
+
+/// Bevy plugin to sync revealed clues over a [`ClueTransport`].
+///
+/// In [`Mode::DM`] and [`Mode::Server`] every [`ClueRevealed`] event, e.g.
+/// from the [`RevealPlugin`](crate::reveal::RevealPlugin), is turned into a
+/// [`ClueStatePacket`] and any packet events are sent over the transport.
+/// In [`Mode::Player`] received packets are applied to all the loaded
+/// [`Clues`].
+pub struct ClueSyncPlugin<T> {
+    mode: Mode,
+    transport: T,
+}
+
+impl<T: ClueTransport + Clone> ClueSyncPlugin<T> {
+    /// Create the plugin for the given [`Mode`]
+    pub fn new(mode: Mode, transport: T) -> Self {
+        Self { mode, transport }
+    }
+}
+
+impl<T: ClueTransport + Clone> Plugin for ClueSyncPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Transport(self.transport.clone()));
+        match self.mode {
+            Mode::DM | Mode::Server => {
+                app.add_event::<ClueStatePacket>()
+                    .add_event::<ClueRevealed>()
+                    .add_system(packets_from_reveals)
+                    .add_system(send_clue_states::<T>);
+            }
+            Mode::Player => {
+                app.add_system(receive_clue_states::<T>);
+            }
+        }
+    }
+}
+
+fn packets_from_reveals(
+    mut revealed: EventReader<ClueRevealed>,
+    mut packets: EventWriter<ClueStatePacket>,
+) {
+    for ClueRevealed { id } in revealed.iter() {
+        packets.send(ClueStatePacket { id: id.clone() });
+    }
+}
+
+fn send_clue_states<T: ClueTransport>(
+    mut events: EventReader<ClueStatePacket>,
+    transport: Res<Transport<T>>,
+) {
+    for packet in events.iter() {
+        transport.0.send(packet);
+    }
+}
+
+fn receive_clue_states<T: ClueTransport>(
+    transport: Res<Transport<T>>,
+    mut assets: ResMut<Assets<Clues>>,
+) {
+    for packet in transport.0.recv() {
+        // Only touch the assets that actually change so we don't
+        // send out modified events for every one of them.
+        let changed: Vec<_> = assets
+            .iter()
+            .filter(|(_, clues)| clues.get(&packet.id).is_some_and(|c| !c.known))
+            .map(|(handle, _)| handle)
+            .collect();
+        for handle in changed {
+            if let Some(clues) = assets.get_mut(&Handle::weak(handle)) {
+                clues.set_known(&packet.id, true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use bevy::{
+        asset::{AddAsset, AssetPlugin},
+        prelude::MinimalPlugins,
+    };
+
+    use super::*;
+
+    /// Delivers every packet sent to whoever calls `recv`
+    #[derive(Clone, Default)]
+    struct InMemory(Arc<Mutex<Vec<ClueStatePacket>>>);
+
+    impl ClueTransport for InMemory {
+        fn send(&self, packet: &ClueStatePacket) {
+            self.0.lock().unwrap().push(packet.clone());
+        }
+
+        fn recv(&self) -> Vec<ClueStatePacket> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    fn id(id: &str) -> ClueId {
+        serde_yaml::from_str(id).unwrap()
+    }
+
+    #[test]
+    fn reveal_round_trips_to_player() {
+        let transport = InMemory::default();
+
+        let mut dm = App::new();
+        dm.add_plugin(ClueSyncPlugin::new(Mode::DM, transport.clone()));
+        dm.world.send_event(ClueRevealed { id: id("knife") });
+        dm.update();
+        dm.update();
+        assert_eq!(
+            *transport.0.lock().unwrap(),
+            [ClueStatePacket { id: id("knife") }]
+        );
+
+        let mut player = App::new();
+        player
+            .add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Clues>()
+            .add_plugin(ClueSyncPlugin::new(Mode::Player, transport));
+        let clues = Clues::from_reader(
+            "clues:\n- {id: knife, information: x, persons: [], locations: []}\n".as_bytes(),
+        )
+        .unwrap();
+        let handle = player.world.resource_mut::<Assets<Clues>>().add(clues);
+        player.update();
+        let clues = player.world.resource::<Assets<Clues>>();
+        assert!(clues.get(&handle).unwrap().get(&id("knife")).unwrap().known);
+    }
+}
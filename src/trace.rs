@@ -0,0 +1,124 @@
+//! Tracing spans for the slower queries, only compiled in with the `trace`
+//! feature
+use bevy::utils::{
+    tracing::{self, field, span::EnteredSpan},
+    Instant,
+};
+
+/// A span covering one query, opened at the top of the query method so
+/// the time spent building indices and intersections is included
+pub(crate) struct QuerySpan {
+    span: EnteredSpan,
+    started: Instant,
+}
+
+impl QuerySpan {
+    /// Open and enter the span, starting the timer
+    pub(crate) fn start(query: &'static str) -> Self {
+        let span = tracing::info_span!(
+            "clue_query",
+            query,
+            count = field::Empty,
+            elapsed_us = field::Empty
+        );
+        Self {
+            span: span.entered(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Run the rest of the query by collecting its results while the span
+    /// is still entered, then record how many there were and how long the
+    /// whole query took. Time the caller spends using the results isn't
+    /// counted.
+    pub(crate) fn finish<I: IntoIterator>(self, results: I) -> std::vec::IntoIter<I::Item> {
+        let results: Vec<_> = results.into_iter().collect();
+        self.span.record("count", results.len());
+        self.span
+            .record("elapsed_us", self.started.elapsed().as_micros() as u64);
+        results.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    };
+
+    use bevy::utils::tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    use crate::{clues::Clues, locations::Locations};
+
+    /// Records the fields of every span as `name=value` strings
+    #[derive(Default)]
+    struct Recorder {
+        next_id: AtomicU64,
+        fields: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct Fields<'a>(&'a Mutex<Vec<String>>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            let value = format!("{:?}", value);
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}={}", field.name(), value.trim_matches('"')));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            span.record(&mut Fields(&self.fields));
+            span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &span::Id, values: &span::Record<'_>) {
+            values.record(&mut Fields(&self.fields));
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn location_tree_query_records_a_span() {
+        let clues =
+            Clues::from_reader("clues:\n- {id: a, information: x, persons: [], locations: [inn]}\n- {id: b, information: x, persons: [], locations: [town]}\n".as_bytes())
+                .unwrap();
+        let locations = Locations::from_reader(
+            "locations:
+- {id: town, name: Town, parent_locations: [], info: null}
+- {id: inn, name: Inn, parent_locations: [town], info: null}
+"
+            .as_bytes(),
+        )
+        .unwrap();
+        let recorder = Recorder::default();
+        let fields = recorder.fields.clone();
+        let town = serde_yaml::from_str("town").unwrap();
+        let count = bevy::utils::tracing::subscriber::with_default(recorder, || {
+            clues.get_in_location_tree(&locations, &town).count()
+        });
+        assert_eq!(count, 2);
+        let fields = fields.lock().unwrap();
+        assert!(fields.contains(&"query=get_in_location_tree".to_string()));
+        assert!(fields.contains(&"count=2".to_string()));
+        assert!(fields.iter().any(|f| f.starts_with("elapsed_us=")));
+    }
+}
@@ -0,0 +1,248 @@
+//! Optional checks run on data files as they are loaded
+use bevy::prelude::warn;
+#[cfg(feature = "strict-ids")]
+use std::sync::{OnceLock, RwLock};
+
+#[cfg(feature = "strict-ids")]
+use regex::Regex;
+
+/// Checks for text in the data files that is most likely a copy-paste
+/// mistake. Problems are logged as warnings, they never fail the load.
+#[derive(Debug, Clone, Copy)]
+pub struct TextValidation {
+    /// The longest a piece of text can be, in characters
+    pub max_length: usize,
+}
+
+impl Default for TextValidation {
+    fn default() -> Self {
+        Self { max_length: 2000 }
+    }
+}
+
+impl TextValidation {
+    /// Warn if a piece of text is too long or contains control characters
+    /// other than newlines and tabs. `what` and `id` name where the text
+    /// came from, e.g. `"clue"` and its id.
+    pub(crate) fn check(&self, what: &str, id: &str, text: &str) {
+        let length = text.chars().count();
+        if length > self.max_length {
+            warn!(
+                "The text of {} {:?} is {} characters long, the limit is {}",
+                what, id, length, self.max_length
+            );
+        }
+        if text
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t' && c != '\r')
+        {
+            warn!("The text of {} {:?} contains control characters", what, id);
+        }
+    }
+}
+
+/// The pattern ids are checked against, [`None`] meaning the default
+#[cfg(feature = "strict-ids")]
+static ID_PATTERN: RwLock<Option<Regex>> = RwLock::new(None);
+
+/// [`DEFAULT_ID_PATTERN`], compiled the first time it's needed
+#[cfg(feature = "strict-ids")]
+static DEFAULT_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// The pattern used when [`set_id_pattern`] hasn't been called:
+/// lowercase letters, digits, `_` and `-`
+#[cfg(feature = "strict-ids")]
+pub const DEFAULT_ID_PATTERN: &str = "^[a-z0-9_-]+$";
+
+/// Set the regular expression every clue, person and location id has to
+/// match to be loaded. Only available with the `strict-ids` feature,
+/// without it any id is accepted.
+#[cfg(feature = "strict-ids")]
+pub fn set_id_pattern(pattern: &str) -> Result<(), regex::Error> {
+    let regex = Regex::new(pattern)?;
+    *ID_PATTERN.write().unwrap_or_else(|e| e.into_inner()) = Some(regex);
+    Ok(())
+}
+
+/// Check an id against the pattern set with [`set_id_pattern`]
+#[cfg(feature = "strict-ids")]
+pub(crate) fn check_id(id: String) -> Result<String, String> {
+    // Only a read lock, so loaders checking ids in parallel don't wait on
+    // each other
+    let pattern = ID_PATTERN.read().unwrap_or_else(|e| e.into_inner());
+    let regex = pattern.as_ref().unwrap_or_else(|| {
+        DEFAULT_ID_REGEX
+            .get_or_init(|| Regex::new(DEFAULT_ID_PATTERN).expect("the default pattern is valid"))
+    });
+    if regex.is_match(&id) {
+        Ok(id)
+    } else {
+        Err(format!("id {:?} doesn't match {:?}", id, regex.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex, Once,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use bevy::{
+        asset::{AssetPlugin, AssetServerSettings, LoadState},
+        prelude::{App, AssetServer, Assets, Handle, MinimalPlugins},
+        utils::tracing::{
+            field::{Field, Visit},
+            span, subscriber, Event, Level, Metadata, Subscriber,
+        },
+    };
+
+    use super::*;
+    use crate::clues::{Clues, CluesAssetPlugin};
+
+    /// Counts the warnings logged
+    struct Warnings(Arc<AtomicUsize>);
+
+    impl Subscriber for Warnings {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            if *event.metadata().level() == Level::WARN {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    fn warnings(text: &str) -> usize {
+        let count = Arc::new(AtomicUsize::new(0));
+        subscriber::with_default(Warnings(count.clone()), || {
+            TextValidation { max_length: 10 }.check("location", "inn", text)
+        });
+        count.load(Ordering::Relaxed)
+    }
+
+    /// The message of every warning logged on a thread without its own
+    /// subscriber, loaders run on the task pool so [`Warnings`] can't see
+    /// what they log
+    static GLOBAL_WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct GlobalWarnings;
+
+    struct Message(String);
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{:?}", value);
+            }
+        }
+    }
+
+    impl Subscriber for GlobalWarnings {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            if *event.metadata().level() == Level::WARN {
+                let mut message = Message(String::new());
+                event.record(&mut message);
+                GLOBAL_WARNINGS.lock().unwrap().push(message.0);
+            }
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn suspicious_text_is_warned_about() {
+        assert_eq!(warnings("Fine text\n"), 0);
+        assert_eq!(warnings("Much too long a text"), 1);
+        assert_eq!(warnings("Bell\u{7}"), 1);
+    }
+
+    #[test]
+    fn loaded_clues_are_warned_about() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| subscriber::set_global_default(GlobalWarnings).unwrap());
+
+        let folder = std::env::temp_dir().join(format!(
+            "revealer-{}-loaded_clues_are_warned_about",
+            std::process::id()
+        ));
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(
+            folder.join("hall.clues.yml"),
+            "clues:\n- {id: long_letter, information: Much too long a text, persons: [], locations: []}\n",
+        )
+        .unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(AssetServerSettings {
+            asset_folder: folder.to_string_lossy().into_owned(),
+            watch_for_changes: false,
+        })
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(
+            CluesAssetPlugin::default().with_text_validation(TextValidation { max_length: 10 }),
+        );
+        let handle: Handle<Clues> = app.world.resource::<AssetServer>().load("hall.clues.yml");
+        for _ in 0..500 {
+            app.update();
+            if app.world.resource::<Assets<Clues>>().contains(&handle) {
+                break;
+            }
+            let state = app.world.resource::<AssetServer>().get_load_state(&handle);
+            assert_ne!(state, LoadState::Failed);
+            thread::sleep(Duration::from_millis(10));
+        }
+        fs::remove_dir_all(folder).unwrap();
+
+        // The clue still loads, the warning is all that happens
+        let clues = app.world.resource::<Assets<Clues>>().get(&handle).unwrap();
+        assert!(clues.get_str("long_letter").is_some());
+        assert!(GLOBAL_WARNINGS
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|w| w.contains("clue \"long_letter\" is 20 characters long")));
+    }
+
+    #[cfg(feature = "strict-ids")]
+    #[test]
+    fn default_pattern_rejects_spaces() {
+        assert!(check_id("red_door-2".to_string()).is_ok());
+        assert!(check_id("red door".to_string()).is_err());
+        assert!(serde_yaml::from_str::<crate::clues::ClueId>("red door").is_err());
+    }
+}
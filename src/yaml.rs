@@ -0,0 +1,70 @@
+//! Helpers for reading the YAML data files
+use std::io::Read;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+/// Deserialize a file from YAML, treating an empty document (no content,
+/// only comments or a bare `---`) as the default value
+pub(crate) fn from_slice<T: DeserializeOwned + Default>(bytes: &[u8]) -> serde_yaml::Result<T> {
+    Ok(serde_yaml::from_slice::<Option<T>>(bytes)?.unwrap_or_default())
+}
+
+/// Same as [`from_slice`], but reading from a [`Read`]er
+pub(crate) fn from_reader<T: DeserializeOwned + Default, R: Read>(
+    reader: R,
+) -> serde_yaml::Result<T> {
+    Ok(serde_yaml::from_reader::<_, Option<T>>(reader)?.unwrap_or_default())
+}
+
+/// Deserialize a list, treating `null` (e.g. `clues:` with nothing after
+/// it) as an empty list
+pub(crate) fn list_or_null<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{clues::Clues, locations::Locations};
+
+    #[test]
+    fn zero_byte_files_are_empty() {
+        assert_eq!(Clues::from_reader(&b""[..]).unwrap().iter().count(), 0);
+        assert_eq!(Locations::from_reader(&b""[..]).unwrap().iter().count(), 0);
+        assert_eq!(
+            Clues::from_reader(&b"# todo\n---\n"[..])
+                .unwrap()
+                .iter()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn empty_lists_are_empty() {
+        for yaml in ["clues:\n", "clues: []\n"] {
+            assert_eq!(
+                Clues::from_reader(yaml.as_bytes()).unwrap().iter().count(),
+                0
+            );
+        }
+        for yaml in ["locations:\n", "locations: []\n"] {
+            assert_eq!(
+                Locations::from_reader(yaml.as_bytes())
+                    .unwrap()
+                    .iter()
+                    .count(),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_files_still_fail() {
+        assert!(Clues::from_reader(&b"clues: [\n"[..]).is_err());
+        assert!(Locations::from_reader(&b"locations: 3\n"[..]).is_err());
+    }
+}